@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use crate::model::Word;
+
+/// Gaps between words longer than this are treated as silence, not speaking time.
+const MAX_PAUSE_SECS: f64 = 2.0;
+
+/// Window used for the live `speaking_rate_live` estimate.
+pub const LIVE_WINDOW_SECS: f64 = 30.0;
+
+/// 🗣️ Speaking-rate summary returned by `speaking_rate`
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SpeakingRate {
+    pub wpm: f64,
+    pub total_words: usize,
+    pub speaking_secs: f64,
+}
+
+/// Words per minute over the actual speaking time (long silences excluded).
+pub fn speaking_rate(words: &[Word]) -> SpeakingRate {
+    let mut speaking_secs = 0.0;
+    let mut prev_end: Option<f64> = None;
+
+    for w in words {
+        speaking_secs += (w.end - w.start).max(0.0);
+        if let Some(end) = prev_end {
+            let gap = w.start - end;
+            if gap > 0.0 && gap <= MAX_PAUSE_SECS {
+                speaking_secs += gap;
+            }
+        }
+        prev_end = Some(w.end);
+    }
+
+    let wpm = if speaking_secs > 0.0 {
+        words.len() as f64 / (speaking_secs / 60.0)
+    } else {
+        0.0
+    };
+
+    SpeakingRate { wpm, total_words: words.len(), speaking_secs }
+}
+
+/// Words per minute over the trailing `window_secs` of speech.
+pub fn live_wpm(words: &[Word], window_secs: f64) -> f64 {
+    let Some(last) = words.last() else { return 0.0 };
+    let cutoff = last.end - window_secs;
+    let first = words.iter().position(|w| w.start >= cutoff).unwrap_or(words.len());
+    speaking_rate(&words[first..]).wpm
+}
@@ -0,0 +1,90 @@
+use std::sync::Mutex;
+
+/// RMS (0..1 of full scale) auto-gain steers toward
+const AUTO_TARGET_RMS: f32 = 0.1;
+/// Auto-gain never boosts beyond this, so room noise isn't blown up during silence
+const AUTO_MAX_GAIN: f32 = 10.0;
+const AUTO_MIN_GAIN: f32 = 0.25;
+/// Per-frame smoothing of the level estimate (fast attack, slow release)
+const AUTO_ATTACK: f32 = 0.5;
+const AUTO_RELEASE: f32 = 0.05;
+/// Frames quieter than this don't move the auto-gain (nothing to normalize)
+const AUTO_FLOOR_RMS: f32 = 0.005;
+/// Above this fraction of full scale samples are compressed instead of hard-clipped
+const SOFT_KNEE: f32 = 0.8;
+
+/// 🔊 Input gain applied to captured frames before metering, resampling and sending
+pub struct Gain {
+    fixed: f32,
+    /// Smoothed level estimate for auto-gain; `None` when auto-gain is off
+    auto_level: Option<f32>,
+}
+
+impl Gain {
+    pub fn new(fixed: f32, auto: bool) -> Self {
+        Gain { fixed, auto_level: auto.then_some(AUTO_TARGET_RMS) }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.fixed == 1.0 && self.auto_level.is_none()
+    }
+
+    /// Current overall gain factor
+    fn factor(&self) -> f32 {
+        let auto = self
+            .auto_level
+            .map(|level| (AUTO_TARGET_RMS / level).clamp(AUTO_MIN_GAIN, AUTO_MAX_GAIN))
+            .unwrap_or(1.0);
+        self.fixed * auto
+    }
+
+    /// Scale `samples` in place
+    pub fn apply(&mut self, samples: &mut [i16]) {
+        if self.is_identity() || samples.is_empty() {
+            return;
+        }
+        if let Some(level) = self.auto_level.as_mut() {
+            let rms = frame_rms(samples) * self.fixed;
+            if rms > AUTO_FLOOR_RMS {
+                let rate = if rms > *level { AUTO_ATTACK } else { AUTO_RELEASE };
+                *level += (rms - *level) * rate;
+            }
+        }
+        let g = self.factor();
+        for s in samples.iter_mut() {
+            *s = soft_clip(*s as f32 * g);
+        }
+    }
+}
+
+fn frame_rms(samples: &[i16]) -> f32 {
+    let sum: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    ((sum / samples.len() as f64).sqrt() / i16::MAX as f64) as f32
+}
+
+/// Linear below the knee, then smoothly approaching full scale (no wrap-around, no hard edge)
+fn soft_clip(x: f32) -> i16 {
+    let full = i16::MAX as f32;
+    let knee = SOFT_KNEE * full;
+    let mag = x.abs();
+    let out = if mag <= knee {
+        mag
+    } else {
+        knee + (full - knee) * ((mag - knee) / (full - knee)).tanh()
+    };
+    (out.copysign(x).round()).clamp(i16::MIN as f32, full) as i16
+}
+
+static GAIN: Mutex<Option<Gain>> = Mutex::new(None);
+
+/// Set the gain for the session being started (`1.0` without auto-gain disables it)
+pub fn configure(fixed: f32, auto: bool) {
+    *GAIN.lock().unwrap() = Some(Gain::new(fixed, auto));
+}
+
+/// Apply the session gain to a captured frame
+pub fn apply(samples: &mut [i16]) {
+    if let Some(gain) = GAIN.lock().unwrap().as_mut() {
+        gain.apply(samples);
+    }
+}
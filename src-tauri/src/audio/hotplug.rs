@@ -0,0 +1,167 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Device-change bursts (docking stations, Bluetooth reconnects) within this window
+/// are coalesced into one `devices_changed` event.
+pub const DEVICE_SETTLE_WINDOW: Duration = Duration::from_millis(500);
+/// How often the watcher re-enumerates input devices: at least twice per settle
+/// window, so a burst is seen while it is still settling rather than a poll later
+const POLL_INTERVAL: Duration = Duration::from_millis(DEVICE_SETTLE_WINDOW.as_millis() as u64 / 2);
+
+/// 🔌 Coalesces raw device-list observations into settled changes.
+///
+/// Every observation restarts the settle timer; [`DeviceDebouncer::settled`] only
+/// reports a list once it has stopped changing for the window and differs from the
+/// last one reported.
+pub struct DeviceDebouncer {
+    window: Duration,
+    last_reported: Vec<String>,
+    pending: Option<(Vec<String>, Instant)>,
+}
+
+impl DeviceDebouncer {
+    pub fn new(initial: Vec<String>, window: Duration) -> Self {
+        DeviceDebouncer { window, last_reported: initial, pending: None }
+    }
+
+    /// Record the device list as currently enumerated
+    pub fn observe(&mut self, devices: Vec<String>, now: Instant) {
+        match &self.pending {
+            Some((pending, _)) if *pending == devices => {}
+            _ if self.pending.is_none() && devices == self.last_reported => {}
+            _ => self.pending = Some((devices, now)),
+        }
+    }
+
+    /// The settled list, if the burst is over and something actually changed
+    pub fn settled(&mut self, now: Instant) -> Option<Vec<String>> {
+        let (_, since) = self.pending.as_ref()?;
+        if now.duration_since(*since) < self.window {
+            return None;
+        }
+        let (devices, _) = self.pending.take()?;
+        if devices == self.last_reported {
+            return None;
+        }
+        self.last_reported = devices.clone();
+        Some(devices)
+    }
+}
+
+/// Whether a settled change should move the recording to another device:
+/// only when the active one is actually gone, never just because the list changed.
+pub fn needs_switch(active_device: &str, devices: &[String]) -> bool {
+    !active_device.is_empty() && !devices.iter().any(|d| d == active_device)
+}
+
+/// Stop flag of the running device watcher
+static WATCHER: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// 🔌 Start polling the input device list; every settled change is emitted as
+/// `devices_changed` and passed to `on_change`. No-op if already running.
+pub fn start_watcher<F>(app: AppHandle, on_change: F)
+where
+    F: Fn(&[String]) + Send + 'static,
+{
+    let mut guard = WATCHER.lock().unwrap();
+    if guard.is_some() {
+        return;
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    *guard = Some(stop.clone());
+
+    std::thread::spawn(move || {
+        // Reopened only when the host selection changes; reopening it every poll is
+        // expensive on some backends
+        let mut host_changes = super::host_changes();
+        let (mut host, _) = super::select_host();
+        let mut debouncer = DeviceDebouncer::new(enumerate(&host), DEVICE_SETTLE_WINDOW);
+        println!("🔌 Device watcher started");
+
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+            if super::host_changes() != host_changes {
+                host_changes = super::host_changes();
+                host = super::select_host().0;
+                println!("🔌 Device watcher switched to host {:?}", host.id());
+            }
+            let now = Instant::now();
+            debouncer.observe(enumerate(&host), now);
+            if let Some(devices) = debouncer.settled(now) {
+                println!("🔌 Input devices changed: {:?}", devices);
+                let _ = app.emit("devices_changed", &devices);
+                on_change(&devices);
+            }
+        }
+        println!("🔌 Device watcher stopped");
+    });
+}
+
+fn enumerate(host: &cpal::Host) -> Vec<String> {
+    host.input_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// 🔌 Stop the device watcher (it exits within one poll interval)
+pub fn stop_watcher() {
+    if let Some(stop) = WATCHER.lock().unwrap().take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn burst_of_changes_is_reported_once() {
+        let t0 = Instant::now();
+        let mut debouncer = DeviceDebouncer::new(list(&["Mic"]), ms(500));
+
+        debouncer.observe(list(&["Mic", "Dock"]), t0);
+        debouncer.observe(list(&["Mic", "Dock", "Headset"]), t0 + ms(100));
+        // an unchanged list doesn't restart the window
+        debouncer.observe(list(&["Mic", "Dock", "Headset"]), t0 + ms(300));
+        assert_eq!(debouncer.settled(t0 + ms(400)), None);
+
+        assert_eq!(debouncer.settled(t0 + ms(600)), Some(list(&["Mic", "Dock", "Headset"])));
+        debouncer.observe(list(&["Mic", "Dock", "Headset"]), t0 + ms(700));
+        assert_eq!(debouncer.settled(t0 + ms(2000)), None);
+    }
+
+    #[test]
+    fn change_that_reverts_is_not_reported() {
+        let t0 = Instant::now();
+        let mut debouncer = DeviceDebouncer::new(list(&["A"]), ms(500));
+
+        debouncer.observe(list(&["B"]), t0);
+        debouncer.observe(list(&["A"]), t0 + ms(100));
+        assert_eq!(debouncer.settled(t0 + ms(1000)), None);
+        assert_eq!(debouncer.settled(t0 + ms(2000)), None);
+    }
+
+    #[test]
+    fn each_change_restarts_the_window() {
+        let t0 = Instant::now();
+        let mut debouncer = DeviceDebouncer::new(list(&["A"]), ms(500));
+
+        debouncer.observe(list(&["B"]), t0);
+        debouncer.observe(list(&["C"]), t0 + ms(400));
+        // 500 ms after the first change, but only 100 ms after the last
+        assert_eq!(debouncer.settled(t0 + ms(500)), None);
+        assert_eq!(debouncer.settled(t0 + ms(899)), None);
+        assert_eq!(debouncer.settled(t0 + ms(900)), Some(list(&["C"])));
+    }
+}
@@ -0,0 +1,232 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Minimum time between meter events (~20 Hz); the callback fires far more often
+pub const EMIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Latest combined RMS level (`f32` bits), read by `get_audio_level`
+static LEVEL: AtomicU32 = AtomicU32::new(0);
+
+/// Publish the latest normalized level (0.0..=1.0)
+pub fn set_level(level: f32) {
+    LEVEL.store(level.to_bits(), Ordering::Relaxed);
+}
+
+/// 📊 The latest normalized level, 0.0 when nothing is being captured
+pub fn level() -> f32 {
+    f32::from_bits(LEVEL.load(Ordering::Relaxed))
+}
+
+/// Lets at most one meter event through per [`EMIT_INTERVAL`]
+#[derive(Debug, Default)]
+pub struct EmitGate {
+    last: Option<Instant>,
+}
+
+impl EmitGate {
+    /// Whether an event may be emitted at `now` (and if so, start a new interval)
+    pub fn ready(&mut self, now: Instant) -> bool {
+        if self.last.is_some_and(|last| now.duration_since(last) < EMIT_INTERVAL) {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+/// 📊 Level reading for a single channel (both normalized to 0.0..=1.0)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ChannelLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Payload of the `audio_meter_multi` event
+#[derive(Debug, Clone, Serialize)]
+pub struct MultiChannelMeter {
+    pub channels: Vec<ChannelLevel>,
+}
+
+/// Default smoothing: rise quickly on speech, fall off over a few hundred ms in pauses
+const DEFAULT_ATTACK: f32 = 0.6;
+const DEFAULT_RELEASE: f32 = 0.08;
+
+/// Per-buffer smoothing coefficients (1.0 = follow the raw level)
+#[derive(Debug, Clone, Copy)]
+pub struct Smoothing {
+    /// Used while the level rises
+    pub attack: f32,
+    /// Used while the level falls
+    pub release: f32,
+}
+
+static SMOOTHING: Mutex<Smoothing> = Mutex::new(Smoothing { attack: DEFAULT_ATTACK, release: DEFAULT_RELEASE });
+
+/// 🎚️ Change the meter's attack/release coefficients (each in `0.0 < x <= 1.0`)
+pub fn set_smoothing(attack: f32, release: f32) -> Result<(), String> {
+    for (name, value) in [("attack", attack), ("release", release)] {
+        if !(value > 0.0 && value <= 1.0) {
+            return Err(format!("{} must be in (0, 1], got {}", name, value));
+        }
+    }
+    *SMOOTHING.lock().unwrap() = Smoothing { attack, release };
+    Ok(())
+}
+
+fn smoothing() -> Smoothing {
+    *SMOOTHING.lock().unwrap()
+}
+
+/// Exponential smoothing of the level across callback buffers
+#[derive(Debug, Default)]
+pub struct LevelSmoother {
+    value: f32,
+}
+
+impl LevelSmoother {
+    /// Move towards `level` with the current attack/release coefficient and return the smoothed level
+    pub fn next(&mut self, level: f32) -> f32 {
+        let Smoothing { attack, release } = smoothing();
+        let coeff = if level > self.value { attack } else { release };
+        self.value += coeff * (level - self.value);
+        self.value
+    }
+}
+
+/// 📊 Metering of captured frames, shared by the in-process and worker capture paths:
+/// every frame updates the smoothed [`level`]; `audio_meter`/`audio_meter_multi`
+/// and `audio_level` are throttled to one per [`EMIT_INTERVAL`]
+pub struct FrameMeter {
+    app: Option<AppHandle>,
+    channels: usize,
+    decimation: usize,
+    gate: EmitGate,
+    smoother: LevelSmoother,
+}
+
+impl FrameMeter {
+    /// Meter `channels`-channel interleaved frames, looking at every `decimation`-th frame
+    pub fn new(app: Option<AppHandle>, channels: usize, decimation: usize) -> Self {
+        FrameMeter {
+            app,
+            channels: channels.max(1),
+            decimation: decimation.max(1),
+            gate: EmitGate::default(),
+            smoother: LevelSmoother::default(),
+        }
+    }
+
+    /// Meter one (already gained) frame
+    pub fn measure(&mut self, samples: &[i16]) {
+        let levels = channel_levels_decimated(samples, self.channels, self.decimation);
+        let raw = levels.iter().map(|l| l.rms).fold(0.0f32, f32::max);
+        let combined = self.smoother.next(raw);
+        set_level(combined);
+        let Some(app) = &self.app else { return };
+        if self.gate.ready(Instant::now()) {
+            if self.channels > 1 {
+                let _ = app.emit("audio_meter_multi", MultiChannelMeter { channels: levels });
+            } else {
+                let _ = app.emit("audio_meter", levels[0]);
+            }
+            // keep the combined `audio_level` for existing listeners
+            let _ = app.emit("audio_level", combined);
+        }
+    }
+
+    /// A frame dropped while paused: let the level decay towards silence
+    pub fn silence(&mut self) {
+        set_level(self.smoother.next(0.0));
+    }
+}
+
+/// Compute RMS and peak for every channel of an interleaved buffer.
+pub fn channel_levels(interleaved: &[i16], channels: usize) -> Vec<ChannelLevel> {
+    channel_levels_decimated(interleaved, channels, 1)
+}
+
+/// Like [`channel_levels`], but only looks at every `decimation`-th frame.
+///
+/// Meant for metering in the audio callback, where the visual level doesn't need
+/// full resolution; the buffer itself is left untouched.
+pub fn channel_levels_decimated(interleaved: &[i16], channels: usize, decimation: usize) -> Vec<ChannelLevel> {
+    let channels = channels.max(1);
+    let mut sum_sq = vec![0f64; channels];
+    let mut peak = vec![0i32; channels];
+    let mut frames = 0usize;
+
+    for frame in interleaved.chunks_exact(channels).step_by(decimation.max(1)) {
+        for (ch, s) in frame.iter().enumerate() {
+            let v = *s as f64;
+            sum_sq[ch] += v * v;
+            peak[ch] = peak[ch].max((*s as i32).abs());
+        }
+        frames += 1;
+    }
+
+    (0..channels)
+        .map(|ch| {
+            if frames == 0 {
+                return ChannelLevel { rms: 0.0, peak: 0.0 };
+            }
+            let rms = (sum_sq[ch] / frames as f64).sqrt() / (i16::MAX as f64);
+            let peak = peak[ch] as f64 / (i16::MAX as f64);
+            ChannelLevel {
+                rms: (rms as f32).clamp(0.0, 1.0),
+                peak: (peak as f32).clamp(0.0, 1.0),
+            }
+        })
+        .collect()
+}
+
+/// Convert interleaved audio from `from` to `to` channels: downmix to mono,
+/// duplicate a mono signal, or keep the first `to` channels
+pub fn remix(interleaved: &[i16], from: usize, to: usize) -> Vec<i16> {
+    if to <= 1 {
+        return downmix_to_mono(interleaved, from);
+    }
+    if from == to {
+        return interleaved.to_vec();
+    }
+    if from <= 1 {
+        return interleaved.iter().flat_map(|&s| std::iter::repeat_n(s, to)).collect();
+    }
+    interleaved
+        .chunks_exact(from)
+        .flat_map(|frame| (0..to).map(move |c| frame[c.min(from - 1)]))
+        .collect()
+}
+
+/// Average interleaved channels down to a single mono channel.
+pub fn downmix_to_mono(interleaved: &[i16], channels: usize) -> Vec<i16> {
+    if channels <= 1 {
+        return interleaved.to_vec();
+    }
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| {
+            let sum: i32 = frame.iter().map(|s| *s as i32).sum();
+            (sum / channels as i32) as i16
+        })
+        .collect()
+}
+
+/// 🎚️ Calibration statistics for a captured sample
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SampleStats {
+    pub peak: f32,
+    pub rms: f32,
+    /// Samples at (or within one step of) full scale
+    pub clipped: usize,
+    pub sample_count: usize,
+}
+
+/// Compute peak/RMS/clipping over a raw sample buffer.
+pub fn sample_stats(samples: &[i16]) -> SampleStats {
+    let level = channel_levels(samples, 1)[0];
+    let clipped = samples.iter().filter(|s| (**s as i32).abs() >= i16::MAX as i32 - 1).count();
+    SampleStats { peak: level.peak, rms: level.rms, clipped, sample_count: samples.len() }
+}
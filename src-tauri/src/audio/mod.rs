@@ -4,41 +4,129 @@ use cpal::{
 };
 use tauri::AppHandle;
 use tauri::Emitter;
-use std::sync::{mpsc, OnceLock, Arc};
+use std::sync::{mpsc, OnceLock, Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub mod hotplug;
+pub mod gain;
+pub mod meter;
+pub mod queue;
+
+/// What [`AudioCommand::Start`] asks to capture
+struct StartRequest {
+    device_name: Option<String>,
+    meter_channels: u16,
+    requested_rate: Option<u32>,
+    stereo: bool,
+    on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    app: Option<AppHandle>,
+}
 
 enum AudioCommand {
     Start {
-        device_name: Option<String>,
-        on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
-        app: Option<AppHandle>,
-        resp: Option<std::sync::mpsc::Sender<u32>>,
+        request: StartRequest,
+        /// Receives (sample rate, forwarded channels) once the stream is playing, or why it couldn't start
+        resp: Option<std::sync::mpsc::Sender<Result<(u32, u16), String>>>,
     },
     Stop,
+    /// The device behind stream `generation` went away (sent from the stream's error callback)
+    DeviceLost { generation: u64 },
+    /// The device watcher saw a new input device list
+    DevicesChanged(Vec<String>),
+}
+
+/// Attempts to reopen capture on the default device after the active one disappears
+const RECOVERY_ATTEMPTS: u32 = 3;
+/// Gives the OS time to pick a new default device between attempts
+const RECOVERY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// What the running capture was started with, so it can be rebuilt on another device
+struct Capture {
+    device: String,
+    on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    app: Option<AppHandle>,
+    /// Channels opened on the device (meter channels / stereo)
+    wanted_channels: u16,
+    /// The Deepgram side was set up for this rate and channel count; a new device must match
+    sample_rate: u32,
+    forward_channels: u16,
+    decimation: usize,
 }
 
 static AUDIO_CMD_SENDER: OnceLock<mpsc::Sender<AudioCommand>> = OnceLock::new();
 
-/// 🎙️ List all input devices
-pub fn list_input_devices() -> Vec<String> {
-    // Prefer PulseAudio host when available; it often avoids ALSA timestamp/device problems.
-    let mut preferred_host = None;
-    for id in cpal::available_hosts() {
-        let name = format!("{:?}", id).to_lowercase();
-        if name.contains("pulse") || name.contains("pulseaudio") {
-            preferred_host = Some(id);
-            break;
+/// Bumped by [`set_host`], so long-lived users of a host (the device watcher) reopen it
+static HOST_CHANGES: AtomicU64 = AtomicU64::new(0);
+
+/// Pick the host devices are listed and captured from, plus the reason it was chosen.
+///
+/// The host chosen with [`set_host`] wins; otherwise PulseAudio is preferred when
+/// available, as it often avoids ALSA timestamp/device problems.
+fn select_host() -> (cpal::Host, String) {
+    if let Some(name) = crate::config::load().audio_host {
+        match cpal::available_hosts().into_iter().find(|id| format!("{:?}", id).eq_ignore_ascii_case(&name)) {
+            Some(id) => match cpal::host_from_id(id) {
+                Ok(h) => return (h, format!("{:?} is selected in settings", id)),
+                Err(e) => eprintln!("⚠️ Selected host {:?} failed to open ({}); falling back", id, e),
+            },
+            None => eprintln!("⚠️ Selected host {:?} is not available; falling back", name),
         }
     }
 
-    let host = if let Some(id) = preferred_host {
-        match cpal::host_from_id(id) {
-            Ok(h) => { println!("🌐 Using host: {:?}", id); h }
-            Err(_) => { println!("🌐 Fallback to default host"); cpal::default_host() }
-        }
+    let preferred_host = cpal::available_hosts().into_iter().find(|id| {
+        let name = format!("{:?}", id).to_lowercase();
+        name.contains("pulse") || name.contains("pulseaudio")
+    });
+
+    match preferred_host {
+        Some(id) => match cpal::host_from_id(id) {
+            Ok(h) => (h, format!("{:?} is available and preferred", id)),
+            Err(e) => (cpal::default_host(), format!("{:?} failed to open ({}); using default host", id, e)),
+        },
+        None => (cpal::default_host(), "No PulseAudio host available; using default host".into()),
+    }
+}
+
+/// 🌐 Id of the host [`select_host`] picks (e.g. `Alsa`), for the audio worker's `--host`
+pub fn selected_host_id() -> String {
+    format!("{:?}", select_host().0.id())
+}
+
+/// 🌐 Names of the audio hosts compiled in and available (e.g. `Alsa`, `Jack`)
+pub fn list_hosts() -> Vec<String> {
+    cpal::available_hosts().iter().map(|id| format!("{:?}", id)).collect()
+}
+
+/// 🌐 Remember the host to use from now on (an empty name restores the automatic choice).
+///
+/// Takes effect for the next recording; a running capture keeps its host.
+pub fn set_host(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    let stored = if name.is_empty() {
+        None
     } else {
-        println!("🌐 Using default host");
-        cpal::default_host()
+        let hosts = list_hosts();
+        let found = hosts
+            .iter()
+            .find(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown audio host {:?} (available: {:?})", name, hosts))?;
+        Some(found.clone())
     };
+    println!("🌐 Audio host set to {}", stored.as_deref().unwrap_or("automatic"));
+    crate::config::update(|s| s.audio_host = stored)?;
+    HOST_CHANGES.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// How many times the host selection has changed (see [`set_host`])
+fn host_changes() -> u64 {
+    HOST_CHANGES.load(Ordering::Relaxed)
+}
+
+/// 🎙️ List all input devices
+pub fn list_input_devices() -> Vec<String> {
+    let (host, reason) = select_host();
+    println!("🌐 Using host: {:?} ({})", host.id(), reason);
     host.input_devices()
         .map(|devices| {
             devices
@@ -48,12 +136,98 @@ pub fn list_input_devices() -> Vec<String> {
         .unwrap_or_default()
 }
 
+/// 🩺 Host-selection diagnostics for `audio_backend_info`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BackendInfo {
+    pub selected_host: String,
+    /// Why `selected_host` won
+    pub reason: String,
+    pub available_hosts: Vec<String>,
+    pub default_device: Option<String>,
+    pub device_count: usize,
+}
+
+/// 🩺 Report which host device listing uses and what it sees
+pub fn backend_info() -> BackendInfo {
+    let (host, reason) = select_host();
+    BackendInfo {
+        selected_host: format!("{:?}", host.id()),
+        reason,
+        available_hosts: list_hosts(),
+        default_device: host.default_input_device().and_then(|d| d.name().ok()),
+        device_count: host.input_devices().map(|d| d.count()).unwrap_or(0),
+    }
+}
+
+/// 🎛️ What an input device can capture (`get_device_capabilities`)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceCaps {
+    pub name: String,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// Distinct channel counts across the supported configs, ascending
+    pub channels: Vec<u16>,
+    /// Sample formats, e.g. "i16", "f32"
+    pub sample_formats: Vec<String>,
+    pub default_sample_rate: Option<u32>,
+    /// 16 kHz is supported natively, so the default send rate needs no resampling
+    pub supports_16k: bool,
+}
+
+/// 🎛️ Enumerate `supported_input_configs()` for the named device (same host as `list_input_devices`)
+pub fn device_capabilities(name: &str) -> Result<DeviceCaps, String> {
+    let (host, _) = select_host();
+    let device = host
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?
+        .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+        .ok_or_else(|| format!("Input device not found: {}", name))?;
+    let configs: Vec<_> = device
+        .supported_input_configs()
+        .map_err(|e| format!("Failed to query configs for {}: {}", name, e))?
+        .collect();
+    if configs.is_empty() {
+        return Err(format!("{} reports no supported input configs", name));
+    }
+
+    let mut channels: Vec<u16> = configs.iter().map(|c| c.channels()).collect();
+    channels.sort_unstable();
+    channels.dedup();
+    let mut sample_formats: Vec<String> = Vec::new();
+    for c in &configs {
+        let f = c.sample_format().to_string();
+        if !sample_formats.contains(&f) {
+            sample_formats.push(f);
+        }
+    }
+
+    Ok(DeviceCaps {
+        name: name.to_string(),
+        min_sample_rate: configs.iter().map(|c| c.min_sample_rate().0).min().unwrap_or(0),
+        max_sample_rate: configs.iter().map(|c| c.max_sample_rate().0).max().unwrap_or(0),
+        channels,
+        sample_formats,
+        default_sample_rate: device.default_input_config().ok().map(|c| c.sample_rate().0),
+        supports_16k: configs.iter().any(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&16000)),
+    })
+}
+
 /// 🎙️ Start mic stream (safe fallback)
+///
+/// `meter_channels` > 1 captures that many channels (capped to what the device
+/// offers) so the meter can report per-channel levels; the forwarded audio is
+/// still downmixed to mono, unless `stereo` asks for two interleaved channels.
+/// `requested_rate` opens the stream at that rate when the device supports it
+/// (otherwise its default). Returns the sample rate and channel count of the stream
+/// actually playing, or why none could be started.
 pub fn start_mic_stream_with_device<F>(
     device_name: String,
     app: AppHandle,
+    meter_channels: u16,
+    requested_rate: Option<u32>,
+    stereo: bool,
     on_data: F,
-) -> Option<u32>
+) -> Result<(u32, u16), String>
 where
     F: Fn(Vec<i16>) + Send + Sync + 'static,
 {
@@ -68,24 +242,64 @@ where
 
     let boxed: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static> = Arc::new(on_data);
 
-    let (resp_tx, resp_rx) = std::sync::mpsc::channel::<u32>();
+    let (resp_tx, resp_rx) = std::sync::mpsc::channel::<Result<(u32, u16), String>>();
 
     let _ = sender.send(AudioCommand::Start {
-        device_name: if device_name.trim().is_empty() {
-            None
-        } else {
-            Some(device_name)
+        request: StartRequest {
+            device_name: if device_name.trim().is_empty() {
+                None
+            } else {
+                Some(device_name)
+            },
+            meter_channels: meter_channels.max(1),
+            requested_rate,
+            stereo,
+            on_data: boxed,
+            app: Some(app),
         },
-        on_data: boxed,
-        app: Some(app),
         resp: Some(resp_tx),
     });
 
-    // wait briefly for the audio thread to report the selected sample rate
+    // wait briefly for the audio thread to report the opened sample rate and channels
     use std::time::Duration;
-    match resp_rx.recv_timeout(Duration::from_secs(2)) {
-        Ok(rate) => Some(rate),
-        Err(_) => None,
+    resp_rx
+        .recv_timeout(Duration::from_secs(2))
+        .unwrap_or_else(|_| Err("Timed out waiting for the mic stream to start".into()))
+}
+
+/// The device's default input config, or one at `requested` Hz if the device supports it
+fn input_config(
+    device: &Device,
+    requested: Option<u32>,
+) -> Result<cpal::SupportedStreamConfig, cpal::DefaultStreamConfigError> {
+    let default = device.default_input_config()?;
+    let Some(rate) = requested.filter(|&r| r != default.sample_rate().0) else { return Ok(default) };
+
+    let mut fits: Vec<_> = device
+        .supported_input_configs()
+        .map(|configs| {
+            configs
+                .filter(|c| matches!(c.sample_format(), SampleFormat::I16 | SampleFormat::U16 | SampleFormat::F32))
+                .filter(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&rate))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Stay as close to the default as possible: same sample format, then same channel count
+    fits.sort_by_key(|c| (c.sample_format() != default.sample_format(), c.channels() != default.channels()));
+    match fits.into_iter().next() {
+        Some(c) => Ok(c.with_sample_rate(cpal::SampleRate(rate))),
+        None => {
+            println!("⚠️ {} Hz not supported by this device; using its default {} Hz", rate, default.sample_rate().0);
+            Ok(default)
+        }
+    }
+}
+
+/// 🔌 Tell the capture thread about a new device list, so a recording whose
+/// device vanished moves to the default one
+pub fn notify_devices_changed(devices: Vec<String>) {
+    if let Some(sender) = AUDIO_CMD_SENDER.get() {
+        let _ = sender.send(AudioCommand::DevicesChanged(devices));
     }
 }
 
@@ -97,10 +311,92 @@ pub fn stop_mic_stream() {
     println!("🛑 Mic stream stop requested");
 }
 
+/// 🎚️ Record `secs` seconds from the named (or default) mic and return level statistics.
+///
+/// Opens its own short-lived stream on the calling thread and drops it before returning.
+pub fn capture_sample(device_name: Option<String>, secs: f32) -> Result<meter::SampleStats, String> {
+    if !(secs > 0.0 && secs <= 30.0) {
+        return Err("Sample duration must be between 0 and 30 seconds".into());
+    }
+
+    let (host, _) = select_host();
+    let device = match device_name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => host
+            .input_devices()
+            .ok()
+            .and_then(|mut d| d.find(|dev| dev.name().map(|n| n == name).unwrap_or(false)))
+            .ok_or_else(|| format!("Input device not found: {}", name))?,
+        None => host.default_input_device().ok_or("No input device available on system")?,
+    };
+
+    let config = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get default input config: {}", e))?;
+    let stream_config: StreamConfig = config.clone().into();
+
+    let captured: Arc<Mutex<Vec<i16>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    let on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static> = Arc::new(move |samples: Vec<i16>| {
+        sink.lock().unwrap().extend_from_slice(&samples);
+    });
+
+    let stream = match config.sample_format() {
+        SampleFormat::I16 => build_stream_i16(&device, &stream_config, on_data, log_stream_error),
+        SampleFormat::U16 => build_stream_u16(&device, &stream_config, on_data, log_stream_error),
+        SampleFormat::F32 => build_stream_f32(&device, &stream_config, on_data, log_stream_error),
+        other => return Err(format!("Unsupported sample format: {:?}", other)),
+    }
+    .map_err(|e| format!("Failed to build input stream: {}", e))?;
+
+    stream.play().map_err(|e| format!("Failed to start mic stream: {}", e))?;
+    std::thread::sleep(std::time::Duration::from_secs_f32(secs));
+    // release the device before computing anything
+    drop(stream);
+
+    let samples = captured.lock().unwrap();
+    Ok(meter::sample_stats(&samples))
+}
+
+fn log_stream_error(err: cpal::StreamError) {
+    eprintln!("❌ Mic stream error: {}", err);
+}
+
+/// Error callback of recording stream `generation`: a vanished device (USB mic
+/// unplugged, Bluetooth dropped) is handed to the audio thread for recovery
+fn recording_stream_error(generation: u64) -> impl FnMut(cpal::StreamError) + Send + 'static {
+    move |err| {
+        let lost = matches!(err, cpal::StreamError::DeviceNotAvailable);
+        log_stream_error(err);
+        if lost {
+            if let Some(sender) = AUDIO_CMD_SENDER.get() {
+                let _ = sender.send(AudioCommand::DeviceLost { generation });
+            }
+        }
+    }
+}
+
+/// Build an input stream in the device's sample format
+fn build_stream(
+    device: &Device,
+    config: &StreamConfig,
+    sample_format: SampleFormat,
+    on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    generation: u64,
+) -> Result<cpal::Stream, BuildStreamError> {
+    let on_error = recording_stream_error(generation);
+    match sample_format {
+        SampleFormat::I16 => build_stream_i16(device, config, on_data, on_error),
+        SampleFormat::U16 => build_stream_u16(device, config, on_data, on_error),
+        SampleFormat::F32 => build_stream_f32(device, config, on_data, on_error),
+        _ => Err(BuildStreamError::StreamConfigNotSupported),
+    }
+}
+
 fn build_stream_i16(
     device: &Device,
     config: &StreamConfig,
     on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, BuildStreamError> {
     let cb = on_data.clone();
     device.build_input_stream(
@@ -109,7 +405,7 @@ fn build_stream_i16(
             let samples: Vec<i16> = data.iter().copied().collect();
             (cb)(samples);
         },
-        |err| eprintln!("❌ Mic stream error: {}", err),
+        on_error,
         None,
     )
 }
@@ -118,6 +414,7 @@ fn build_stream_u16(
     device: &Device,
     config: &StreamConfig,
     on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, BuildStreamError> {
     let cb = on_data.clone();
     device.build_input_stream(
@@ -126,7 +423,7 @@ fn build_stream_u16(
             let samples: Vec<i16> = data.iter().map(|s| (*s as i32 - 32768) as i16).collect();
             (cb)(samples);
         },
-        |err| eprintln!("❌ Mic stream error: {}", err),
+        on_error,
         None,
     )
 }
@@ -135,6 +432,7 @@ fn build_stream_f32(
     device: &Device,
     config: &StreamConfig,
     on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, BuildStreamError> {
     let cb = on_data.clone();
     device.build_input_stream(
@@ -143,141 +441,261 @@ fn build_stream_f32(
             let samples: Vec<i16> = data.iter().map(|s| (s * (i16::MAX as f32)) as i16).collect();
             (cb)(samples);
         },
-        |err| eprintln!("❌ Mic stream error: {}", err),
+        on_error,
         None,
     )
 }
 
 fn audio_thread_loop(rx: mpsc::Receiver<AudioCommand>) {
-    let host = cpal::default_host();
+    let (mut host, _) = select_host();
     let mut _current_stream: Option<cpal::Stream> = None;
+    // Bumped for every stream built, so errors from a replaced stream are ignored
+    let mut generation: u64 = 0;
+    let mut capture: Option<Capture> = None;
 
     for cmd in rx {
         match cmd {
-            AudioCommand::Start { device_name, on_data, app, resp } => {
-                let device = if let Some(name) = device_name {
-                    host.input_devices()
-                        .ok()
-                        .and_then(|mut d| d.find(|dev| dev.name().map(|n| n == name).unwrap_or(false)))
-                        .or_else(|| {
-                            println!("⚠️ Selected mic not found, using default");
-                            host.default_input_device()
-                        })
-                } else {
-                    host.default_input_device()
-                };
+            AudioCommand::Start { request, resp } => {
+                // Reselected per recording so `set_host` applies without a restart
+                let (selected, reason) = select_host();
+                println!("🌐 Capturing from host: {:?} ({})", selected.id(), reason);
+                host = selected;
+                generation += 1;
 
-                if let Some(device) = device {
-                    println!("🎤 Using input device: {}", device.name().unwrap_or("Unknown".into()));
-                    // Use the device default input config (safer across ALSA devices).
-                    let config = match device.default_input_config() {
-                        Ok(c) => c,
-                        Err(e) => { eprintln!("❌ Failed to get default input config: {}", e); continue; }
-                    };
-
-                    let mut stream_config: StreamConfig = config.clone().into();
-                    // Force mono to avoid dmix/dsnoop channel mapping issues on some ALSA setups
-                    stream_config.channels = 1;
-
-                    // Before building/playing the stream, report the chosen sample rate back to caller (if requested)
-                    if let Some(tx) = resp {
-                        let _ = tx.send(stream_config.sample_rate.0);
+                let started = open_capture(&host, request, generation);
+                let reply = match started {
+                    Ok((stream, cap)) => {
+                        let reply = Ok((cap.sample_rate, cap.forward_channels));
+                        _current_stream = Some(stream);
+                        capture = Some(cap);
+                        reply
                     }
-
-                    // Debug: list a few supported configs for this device
-                    if let Ok(mut sup) = device.supported_input_configs() {
-                        println!("🔍 Supported configs (first few):");
-                        for (i, c) in sup.by_ref().take(3).enumerate() {
-                            println!("  {}: fmt={:?} min={:?} max={:?}", i, c.sample_format(), c.min_sample_rate(), c.max_sample_rate());
-                        }
+                    Err(e) => {
+                        eprintln!("❌ {}", e);
+                        Err(e)
                     }
+                };
+                if let Some(tx) = resp {
+                    let _ = tx.send(reply);
+                }
+            }
+            AudioCommand::Stop => {
+                _current_stream = None;
+                capture = None;
+                meter::set_level(0.0);
+            }
+            cmd @ (AudioCommand::DeviceLost { .. } | AudioCommand::DevicesChanged(_)) => {
+                let Some(active) = capture.as_ref() else { continue };
+                let lost = match cmd {
+                    // Stale report from a stream that was already replaced
+                    AudioCommand::DeviceLost { generation: lost } => lost == generation,
+                    // Some backends never raise a stream error when a device is unplugged
+                    AudioCommand::DevicesChanged(devices) => hotplug::needs_switch(&active.device, &devices),
+                    _ => false,
+                };
+                if !lost {
+                    continue;
+                }
+                _current_stream = None;
+                let cap = capture.take().unwrap();
+                eprintln!("🔌 Input device disconnected: {}", cap.device);
+                if let Some(ref a) = cap.app {
+                    let _ = a.emit("device_disconnected", serde_json::json!({ "device": cap.device }));
+                }
 
-                    // Wrap the provided `on_data` so we can also emit audio level events
-                    let maybe_app = app.clone();
-                    let orig_cb = on_data.clone();
-                    let wrapper = move |samples: Vec<i16>| {
-                        // compute RMS level
-                        if let Some(ref a) = maybe_app {
-                            if !samples.is_empty() {
-                                let sum_sq: f64 = samples.iter().map(|s| (*s as f64) * (*s as f64)).sum();
-                                let mean = sum_sq / (samples.len() as f64);
-                                let rms = mean.sqrt();
-                                let mut normalized = (rms / (i16::MAX as f64)) as f32;
-                                if normalized.is_nan() { normalized = 0.0 }
-                                if normalized < 0.0 { normalized = 0.0 }
-                                if normalized > 1.0 { normalized = 1.0 }
-                                let _ = a.emit("audio_level", normalized);
-                            } else {
-                                let _ = a.emit("audio_level", 0.0f32);
-                            }
+                generation += 1;
+                match recover_capture(&host, &cap, generation) {
+                    Ok((stream, name)) => {
+                        println!("🔁 Capture resumed on {}", name);
+                        if let Some(ref a) = cap.app {
+                            let _ = a.emit("device_recovered", serde_json::json!({ "device": name }));
                         }
-
-                        (orig_cb)(samples);
-                    };
-
-                    let wrapper_arc: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static> = Arc::new(wrapper);
-
-                    // Use the default config's sample format
-                    let sample_format = config.sample_format();
-
-                    // Debug: print chosen stream config and sample format
-                    println!("🔧 StreamConfig: channels={} sample_rate={} sample_format={:?}", stream_config.channels, stream_config.sample_rate.0, sample_format);
-
-                    // Try to build stream for the selected device
-                    let build_result = match sample_format {
-                        SampleFormat::I16 => build_stream_i16(&device, &stream_config, wrapper_arc.clone()),
-                        SampleFormat::U16 => build_stream_u16(&device, &stream_config, wrapper_arc.clone()),
-                        SampleFormat::F32 => build_stream_f32(&device, &stream_config, wrapper_arc.clone()),
-                        _ => { eprintln!("Unsupported sample format"); continue; }
-                    };
-
-                    let mut stream_opt: Option<cpal::Stream> = None;
-
-                    match build_result {
-                        Ok(s) => stream_opt = Some(s),
-                        Err(e) => {
-                            eprintln!("⚠️ Failed to build stream on selected device: {}", e);
-                            // Attempt fallback: iterate through all input devices and try to build
-                            if let Ok(devices) = host.input_devices() {
-                                for d in devices {
-                                    if d.name().ok() == device.name().ok() { continue; }
-                                    println!("🔁 Trying device: {}", d.name().unwrap_or("unknown".into()));
-                                    if let Ok(def_cfg) = d.default_input_config() {
-                                        let mut def_stream_config: StreamConfig = def_cfg.clone().into();
-                                        def_stream_config.channels = 1; // try mono
-                                        let def_sample_format = def_cfg.sample_format();
-                                        let def_build = match def_sample_format {
-                                            SampleFormat::I16 => build_stream_i16(&d, &def_stream_config, wrapper_arc.clone()),
-                                            SampleFormat::U16 => build_stream_u16(&d, &def_stream_config, wrapper_arc.clone()),
-                                            SampleFormat::F32 => build_stream_f32(&d, &def_stream_config, wrapper_arc.clone()),
-                                            _ => Err(BuildStreamError::StreamConfigNotSupported),
-                                        };
-                                        match def_build {
-                                            Ok(s2) => { stream_opt = Some(s2); break; },
-                                            Err(e2) => eprintln!("  ❌ build failed: {}", e2),
-                                        }
-                                    }
-                                }
-                            }
+                        _current_stream = Some(stream);
+                        capture = Some(Capture { device: name, ..cap });
+                    }
+                    Err(reason) => {
+                        eprintln!("❌ Could not resume capture: {}", reason);
+                        if let Some(ref a) = cap.app {
+                            let _ = a.emit("recording_stopped", serde_json::json!({ "reason": reason }));
                         }
                     }
+                }
+            }
+        }
+    }
+}
+
+/// 🎙️ Open and play the stream for `request`, trying every other input device when
+/// the chosen one can't be opened. Returns the playing stream and what it captures.
+fn open_capture(host: &cpal::Host, request: StartRequest, generation: u64) -> Result<(cpal::Stream, Capture), String> {
+    let StartRequest { device_name, meter_channels, requested_rate, stereo, on_data, app } = request;
+    let device = if let Some(name) = device_name {
+        host.input_devices()
+            .ok()
+            .and_then(|mut d| d.find(|dev| dev.name().map(|n| n == name).unwrap_or(false)))
+            .or_else(|| {
+                println!("⚠️ Selected mic not found, using default");
+                host.default_input_device()
+            })
+    } else {
+        host.default_input_device()
+    };
+    let device = device.ok_or("No input device available on system")?;
+
+    println!("🎤 Using input device: {}", device.name().unwrap_or("Unknown".into()));
+    // Use the device default input config (safer across ALSA devices),
+    // unless a supported rate was requested.
+    let config = input_config(&device, requested_rate).map_err(|e| format!("Failed to get default input config: {}", e))?;
+
+    let mut stream_config: StreamConfig = config.clone().into();
+    // Force mono to avoid dmix/dsnoop channel mapping issues on some ALSA setups,
+    // unless the caller asked for a multichannel meter or stereo capture.
+    let wanted_channels = if stereo { meter_channels.max(2) } else { meter_channels };
+    stream_config.channels = wanted_channels.min(config.channels()).max(1);
+    // What reaches `on_data`: mono, or stereo when the device has two channels
+    let forward_channels: u16 = if stereo && stream_config.channels >= 2 { 2 } else { 1 };
+    if stereo && forward_channels == 1 {
+        println!("⚠️ Stereo requested but the device is mono; capturing mono");
+    }
+
+    // Debug: list a few supported configs for this device
+    if let Ok(mut sup) = device.supported_input_configs() {
+        println!("🔍 Supported configs (first few):");
+        for (i, c) in sup.by_ref().take(3).enumerate() {
+            println!("  {}: fmt={:?} min={:?} max={:?}", i, c.sample_format(), c.min_sample_rate(), c.max_sample_rate());
+        }
+    }
+
+    let decimation = crate::config::load().meter_decimation.max(1);
+    let make_wrapper = |channels: usize, forward_channels: u16| {
+        capture_callback(app.clone(), on_data.clone(), channels, forward_channels, decimation)
+    };
+
+    // Use the default config's sample format
+    let sample_format = config.sample_format();
+
+    // Debug: print chosen stream config and sample format
+    println!("🔧 StreamConfig: channels={} sample_rate={} sample_format={:?}", stream_config.channels, stream_config.sample_rate.0, sample_format);
+
+    if !matches!(sample_format, SampleFormat::I16 | SampleFormat::U16 | SampleFormat::F32) {
+        return Err(format!("Unsupported sample format: {:?}", sample_format));
+    }
+    let wrapper_arc = make_wrapper(stream_config.channels as usize, forward_channels);
+    let build_result = build_stream(&device, &stream_config, sample_format, wrapper_arc, generation);
 
-                    if let Some(s) = stream_opt {
-                        if let Err(e) = s.play() {
-                            eprintln!("❌ Failed to start mic stream: {}", e);
-                        } else {
-                            _current_stream = Some(s);
+    // (stream, device, opened config, forwarded channels) of whichever device worked
+    let opened = match build_result {
+        Ok(s) => Some((s, device.name().unwrap_or("Unknown".into()), stream_config, forward_channels)),
+        Err(e) => {
+            eprintln!("⚠️ Failed to build stream on selected device: {}", e);
+            // Attempt fallback: iterate through all input devices and try to build
+            let mut fallback = None;
+            if let Ok(devices) = host.input_devices() {
+                for d in devices {
+                    if d.name().ok() == device.name().ok() { continue; }
+                    println!("🔁 Trying device: {}", d.name().unwrap_or("unknown".into()));
+                    if let Ok(def_cfg) = d.default_input_config() {
+                        let mut def_stream_config: StreamConfig = def_cfg.clone().into();
+                        def_stream_config.channels = 1; // try mono
+                        let def_sample_format = def_cfg.sample_format();
+                        let def_wrapper = make_wrapper(1, 1);
+                        match build_stream(&d, &def_stream_config, def_sample_format, def_wrapper, generation) {
+                            Ok(s2) => {
+                                fallback = Some((s2, d.name().unwrap_or("unknown".into()), def_stream_config, 1));
+                                break;
+                            }
+                            Err(e2) => eprintln!("  ❌ build failed: {}", e2),
                         }
-                    } else {
-                        eprintln!("❌ Could not build a working input stream on selected or fallback devices");
                     }
-                } else {
-                    eprintln!("❌ No input device available on system");
                 }
             }
-            AudioCommand::Stop => {
-                _current_stream = None;
+            fallback
+        }
+    };
+
+    let Some((stream, device_label, opened_config, forward_channels)) = opened else {
+        return Err("Could not build a working input stream on selected or fallback devices".into());
+    };
+    stream.play().map_err(|e| format!("Failed to start mic stream: {}", e))?;
+    let capture = Capture {
+        device: device_label,
+        on_data,
+        app,
+        wanted_channels: opened_config.channels,
+        sample_rate: opened_config.sample_rate.0,
+        forward_channels,
+        decimation,
+    };
+    Ok((stream, capture))
+}
+
+/// Wrap `on_data` so every captured frame is also gained and metered before it is
+/// forwarded (`channels` captured, remixed to `forward_channels`).
+///
+/// Every frame updates the smoothed [`meter::level`]; the meter events are throttled to ~20 Hz.
+fn capture_callback(
+    app: Option<AppHandle>,
+    on_data: Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static>,
+    channels: usize,
+    forward_channels: u16,
+    decimation: usize,
+) -> Arc<dyn Fn(Vec<i16>) + Send + Sync + 'static> {
+    // Levels are computed on the interleaved (pre-downmix) buffer so a
+    // multichannel meter reflects the real channels.
+    let frame_meter = Mutex::new(meter::FrameMeter::new(app, channels, decimation));
+    Arc::new(move |mut samples: Vec<i16>| {
+        // Paused: drop the frame, and don't meter it either
+        if crate::session::is_paused() {
+            frame_meter.lock().unwrap().silence();
+            return;
+        }
+        // Gain first, so the meter shows what Deepgram will get
+        gain::apply(&mut samples);
+        frame_meter.lock().unwrap().measure(&samples);
+
+        (on_data)(meter::remix(&samples, channels, forward_channels as usize));
+    })
+}
+
+/// 🔁 Reopen capture on the current default device, at the rate the session was started with.
+///
+/// Returns the playing stream and the device name; the error is a user-facing reason.
+fn recover_capture(host: &cpal::Host, cap: &Capture, generation: u64) -> Result<(cpal::Stream, String), String> {
+    let mut reason = String::from("No input device available");
+    for attempt in 1..=RECOVERY_ATTEMPTS {
+        std::thread::sleep(RECOVERY_DELAY);
+        let Some(device) = host.default_input_device() else {
+            println!("⏳ No default input device yet (attempt {}/{})", attempt, RECOVERY_ATTEMPTS);
+            continue;
+        };
+        let name = device.name().unwrap_or("Unknown".into());
+        let config = match input_config(&device, Some(cap.sample_rate)) {
+            Ok(c) => c,
+            Err(e) => {
+                reason = format!("{} has no usable input config: {}", name, e);
+                continue;
             }
+        };
+        if config.sample_rate().0 != cap.sample_rate {
+            // The transcription stream expects the original rate; don't feed it something else
+            return Err(format!("{} cannot capture at {} Hz", name, cap.sample_rate));
+        }
+        let mut stream_config: StreamConfig = config.clone().into();
+        stream_config.channels = cap.wanted_channels.min(config.channels()).max(1);
+        let wrapper = capture_callback(
+            cap.app.clone(),
+            cap.on_data.clone(),
+            stream_config.channels as usize,
+            cap.forward_channels,
+            cap.decimation,
+        );
+        match build_stream(&device, &stream_config, config.sample_format(), wrapper, generation) {
+            Ok(stream) => match stream.play() {
+                Ok(()) => return Ok((stream, name)),
+                Err(e) => reason = format!("Failed to start {}: {}", name, e),
+            },
+            Err(e) => reason = format!("Failed to open {}: {}", name, e),
         }
     }
+    Err(reason)
 }
@@ -0,0 +1,65 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+
+/// Frames buffered between capture and the transcription task: about 5 s of audio
+/// at the usual 10 ms callback size, so a stalled connection can't grow memory forever
+pub const CAPACITY: usize = 512;
+
+/// Payload of `audio_overrun`
+#[derive(Debug, Clone, Serialize)]
+struct Overrun {
+    /// Frames dropped so far this session
+    dropped_frames: u64,
+}
+
+/// 📦 Sending half of the capture → transcription channel.
+///
+/// [`FrameSender::send`] never blocks, as real-time audio callbacks must not: when
+/// the transcription task falls behind and the queue is full, the frame is dropped
+/// and `audio_overrun` is emitted once per overrun.
+#[derive(Clone)]
+pub struct FrameSender {
+    tx: Sender<Vec<i16>>,
+    app: AppHandle,
+    dropped: Arc<AtomicU64>,
+    /// Set while frames are being dropped, so the event fires once per overrun
+    overrun: Arc<AtomicBool>,
+}
+
+/// 📦 A bounded frame channel holding [`CAPACITY`] frames
+pub fn channel(app: AppHandle) -> (FrameSender, Receiver<Vec<i16>>) {
+    let (tx, rx) = mpsc::channel(CAPACITY);
+    let sender = FrameSender {
+        tx,
+        app,
+        dropped: Arc::new(AtomicU64::new(0)),
+        overrun: Arc::new(AtomicBool::new(false)),
+    };
+    (sender, rx)
+}
+
+impl FrameSender {
+    /// Queue `frame` without blocking; false once the receiving task is gone
+    pub fn send(&self, frame: Vec<i16>) -> bool {
+        match self.tx.try_send(frame) {
+            Ok(()) => {
+                if self.overrun.swap(false, Ordering::Relaxed) {
+                    println!("✅ Audio queue recovered ({} frames dropped)", self.dropped.load(Ordering::Relaxed));
+                }
+                true
+            }
+            Err(TrySendError::Full(_)) => {
+                let dropped_frames = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if !self.overrun.swap(true, Ordering::Relaxed) {
+                    eprintln!("⚠️ Audio queue full; dropping frames until transcription catches up");
+                    let _ = self.app.emit("audio_overrun", Overrun { dropped_frames });
+                }
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+}
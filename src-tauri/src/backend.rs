@@ -0,0 +1,59 @@
+use futures_util::future::BoxFuture;
+use tauri::AppHandle;
+use tokio::sync::mpsc::Receiver;
+
+use crate::deepgram::DeepgramBackend;
+use crate::model::FileTranscript;
+use crate::options::{FileOptions, RecordingOptions};
+
+/// Backend used when neither the options nor the caller pick one
+pub const DEFAULT_BACKEND: &str = "deepgram";
+/// Names accepted for `RecordingOptions::backend`
+pub const BACKENDS: &[&str] = &["deepgram", "whisper", "openai"];
+
+/// 🔌 A speech-to-text provider.
+///
+/// Implementations emit the same events as the Deepgram path (`transcript`,
+/// `transcript_words`, ...), so the UI doesn't care which one is running.
+pub trait TranscriptionBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// File-only providers return false and are refused by `start_recording`
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    /// Transcribe live audio until `rx` closes; frames hold `channels` interleaved
+    /// channels at `sample_rate`
+    fn stream(
+        &self,
+        rx: Receiver<Vec<i16>>,
+        app: AppHandle,
+        sample_rate: u32,
+        channels: u16,
+        options: RecordingOptions,
+    ) -> BoxFuture<'static, ()>;
+
+    /// Transcribe a complete audio file; `mime` is `None` when the format should be auto-detected.
+    /// Providers ignore `options` they have no equivalent for.
+    fn transcribe_file(
+        &self,
+        audio: Vec<u8>,
+        mime: Option<&'static str>,
+        options: &FileOptions,
+    ) -> BoxFuture<'static, Result<FileTranscript, String>>;
+}
+
+/// 🔌 Backend by name (`None` → [`DEFAULT_BACKEND`]); fails if it isn't usable,
+/// e.g. its API key is missing
+pub fn select(name: Option<&str>) -> Result<Box<dyn TranscriptionBackend>, String> {
+    match name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(DEFAULT_BACKEND) {
+        "deepgram" => Ok(Box::new(DeepgramBackend::new()?)),
+        #[cfg(feature = "whisper")]
+        "whisper" => Ok(Box::new(crate::whisper::WhisperBackend::new()?)),
+        #[cfg(not(feature = "whisper"))]
+        "whisper" => Err("This build has no local Whisper support (enable the `whisper` feature)".into()),
+        "openai" => Ok(Box::new(crate::openai::OpenAiBackend::new()?)),
+        other => Err(format!("Unknown transcription backend {:?} (available: {:?})", other, BACKENDS)),
+    }
+}
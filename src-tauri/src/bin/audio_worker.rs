@@ -1,25 +1,119 @@
-use cpal::{traits::{DeviceTrait, HostTrait, StreamTrait}, SampleFormat, StreamConfig};
-use std::io::{self, Write};
+use cpal::{traits::{DeviceTrait, HostTrait, StreamTrait}, SampleFormat, StreamConfig, SupportedStreamConfig};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
+use std::time::Duration;
+
+/// Request the parent writes to our stdin before anything else: magic, protocol
+/// version, sample rate (u32 LE, 0 = device default), channels (u16 LE, 0 = mono).
+/// Must match `worker.rs` in the app.
+const REQUEST_MAGIC: &[u8; 4] = b"RQST";
+const REQUEST_LEN: usize = 11;
+/// Also sent in our own header, after the magic
+const PROTOCOL_VERSION: u8 = 1;
+
+/// Set when the worker should stop: SIGTERM/SIGINT, or EOF on stdin (the parent is gone)
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// How often the writer checks `SHUTDOWN` while no audio arrives
+const SHUTDOWN_POLL: Duration = Duration::from_millis(100);
+
+#[cfg(unix)]
+extern "C" fn on_signal(_: libc::c_int) {
+    SHUTDOWN.store(true, Ordering::Relaxed);
+}
+
+/// Turn SIGTERM/SIGINT into a clean shutdown, so the stream is stopped before exiting
+#[cfg(unix)]
+fn install_signal_handlers() {
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // Safety: the handler only stores to an atomic, which is async-signal-safe
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+/// Read the parent's requested (sample rate, channels); `None` fields mean "no preference"
+fn read_request() -> Result<(Option<u32>, u16), String> {
+    let mut request = [0u8; REQUEST_LEN];
+    io::stdin().read_exact(&mut request).map_err(|e| format!("no request on stdin: {}", e))?;
+    if &request[0..4] != REQUEST_MAGIC {
+        return Err("invalid request header".into());
+    }
+    if request[4] != PROTOCOL_VERSION {
+        return Err(format!("unsupported protocol v{} (expected v{})", request[4], PROTOCOL_VERSION));
+    }
+    let rate = u32::from_le_bytes([request[5], request[6], request[7], request[8]]);
+    let channels = u16::from_le_bytes([request[9], request[10]]);
+    Ok(((rate != 0).then_some(rate), channels.max(1)))
+}
+
+/// Open the host named by `--host`, falling back like the app's `audio::select_host`:
+/// PulseAudio when available, otherwise the default host
+fn open_host(name: Option<&str>) -> cpal::Host {
+    if let Some(name) = name {
+        match cpal::available_hosts().into_iter().find(|id| format!("{:?}", id).eq_ignore_ascii_case(name)) {
+            Some(id) => match cpal::host_from_id(id) {
+                Ok(h) => return h,
+                Err(e) => eprintln!("audio_worker: host {:?} failed to open ({}); falling back", id, e),
+            },
+            None => eprintln!("audio_worker: host {:?} is not available; falling back", name),
+        }
+    }
+
+    let preferred_host = cpal::available_hosts().into_iter().find(|id| {
+        let name = format!("{:?}", id).to_lowercase();
+        name.contains("pulse") || name.contains("pulseaudio")
+    });
+    preferred_host
+        .and_then(|id| cpal::host_from_id(id).ok())
+        .unwrap_or_else(cpal::default_host)
+}
+
+/// The parent keeps our stdin open for as long as it wants audio; EOF means it went away
+fn watch_stdin() {
+    std::thread::spawn(|| {
+        let mut stdin = io::stdin();
+        let mut buf = [0u8; 64];
+        while matches!(stdin.read(&mut buf), Ok(n) if n > 0) {}
+        SHUTDOWN.store(true, Ordering::Relaxed);
+    });
+}
 
 fn main() {
+    #[cfg(unix)]
+    install_signal_handlers();
+
     let args: Vec<String> = std::env::args().collect();
+    let mut host_name: Option<String> = None;
     let mut device_name: Option<String> = None;
+    let mut requested_rate: Option<u32> = None;
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
+            "--host" if i + 1 < args.len() => {
+                host_name = Some(args[i + 1].clone());
+                i += 1;
+            }
             "--device" => {
                 if i + 1 < args.len() {
                     device_name = Some(args[i + 1].clone());
                     i += 1;
                 }
             }
+            "--sample-rate" => {
+                if i + 1 < args.len() {
+                    requested_rate = args[i + 1].parse().ok();
+                    i += 1;
+                }
+            }
             _ => {}
         }
         i += 1;
     }
 
-    let host = cpal::default_host();
+    let host = open_host(host_name.as_deref());
     let device = if let Some(name) = device_name {
         host.input_devices()
             .ok()
@@ -37,7 +131,16 @@ fn main() {
         }
     };
 
-    let config = match device.default_input_config() {
+    let (wanted_rate, wanted_channels) = match read_request() {
+        Ok((rate, channels)) => (rate.or(requested_rate), channels),
+        Err(e) => {
+            eprintln!("audio_worker: {}", e);
+            std::process::exit(1);
+        }
+    };
+    watch_stdin();
+
+    let default_config = match device.default_input_config() {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Failed to get default input config: {}", e);
@@ -45,92 +148,40 @@ fn main() {
         }
     };
 
+    let config = choose_config(&device, default_config, wanted_rate, wanted_channels);
     let stream_config: StreamConfig = config.clone().into();
     let sample_rate = stream_config.sample_rate.0;
     let channels = stream_config.channels as usize;
+    // Interleaved channels written per frame: mono is the average of all the device's
+    // channels, otherwise the first `out_channels` of them
+    let out_channels = (wanted_channels as usize).min(channels).max(1);
 
     // channel between audio callback and writer
     let (tx, rx) = mpsc::channel::<Vec<i16>>();
+    let send = move |samples: Vec<i16>| {
+        let _ = tx.send(remix(samples, channels, out_channels));
+    };
 
     // build stream according to sample format
     let stream = match config.sample_format() {
         SampleFormat::I16 => device.build_input_stream(
             &stream_config,
-            move |data: &[i16], _| {
-                // data may be interleaved if channels > 1; convert to mono by
-                // taking the first channel sample from each frame.
-                if channels == 1 {
-                    let v = data.iter().copied().collect::<Vec<i16>>();
-                    let _ = tx.send(v);
-                } else {
-                    let mut v = Vec::with_capacity(data.len() / channels);
-                    for frame_idx in 0..(data.len() / channels) {
-                        let sample = data[frame_idx * channels];
-                        v.push(sample);
-                    }
-                    let _ = tx.send(v);
-                }
-            },
+            move |data: &[i16], _| send(data.to_vec()),
             |e| eprintln!("Audio worker stream error: {}", e),
             None,
         ),
         SampleFormat::U16 => device.build_input_stream(
             &stream_config,
-            move |data: &[u16], _| {
-                if channels == 1 {
-                    let v = data.iter().map(|s| (*s as i32 - 32768) as i16).collect::<Vec<i16>>();
-                    let _ = tx.send(v);
-                } else {
-                    let mut v = Vec::with_capacity(data.len() / channels);
-                    for frame_idx in 0..(data.len() / channels) {
-                        let sample_u = data[frame_idx * channels];
-                        let sample = (sample_u as i32 - 32768) as i16;
-                        v.push(sample);
-                    }
-                    let _ = tx.send(v);
-                }
-            },
-            |e| eprintln!("Audio worker stream error: {}", e),
-            None,
-        ),
-        SampleFormat::F32 => device.build_input_stream(
-            &stream_config,
-            move |data: &[f32], _| {
-                if channels == 1 {
-                    let v = data.iter().map(|s| (s * (i16::MAX as f32)) as i16).collect::<Vec<i16>>();
-                    let _ = tx.send(v);
-                } else {
-                    let mut v = Vec::with_capacity(data.len() / channels);
-                    for frame_idx in 0..(data.len() / channels) {
-                        let sample_f = data[frame_idx * channels];
-                        let sample = (sample_f * (i16::MAX as f32)) as i16;
-                        v.push(sample);
-                    }
-                    let _ = tx.send(v);
-                }
-            },
+            move |data: &[u16], _| send(data.iter().map(|s| (*s as i32 - 32768) as i16).collect()),
             |e| eprintln!("Audio worker stream error: {}", e),
             None,
         ),
-        // `SampleFormat` is non-exhaustive; accept any future/unknown formats by
-        // attempting to interpret them as f32 (safe fallback) to keep the worker
-        // functional on newer cpal versions.
+        // F32, and since `SampleFormat` is non-exhaustive, any future/unknown format:
+        // interpreting them as f32 (safe fallback) keeps the worker functional on
+        // newer cpal versions.
         _ => device.build_input_stream(
             &stream_config,
-            move |data: &[f32], _| {
-                if channels == 1 {
-                    let v = data.iter().map(|s| (s * (i16::MAX as f32)) as i16).collect::<Vec<i16>>();
-                    let _ = tx.send(v);
-                } else {
-                    let mut v = Vec::with_capacity(data.len() / channels);
-                    for frame_idx in 0..(data.len() / channels) {
-                        let sample_f = data[frame_idx * channels];
-                        let sample = (sample_f * (i16::MAX as f32)) as i16;
-                        v.push(sample);
-                    }
-                    let _ = tx.send(v);
-                }
-            },
+            move |data: &[f32], _| send(data.iter().map(|s| (s * (i16::MAX as f32)) as i16).collect()),
             |e| eprintln!("Audio worker stream error: {}", e),
             None,
         ),
@@ -149,28 +200,95 @@ fn main() {
         std::process::exit(3);
     }
 
-    // Writer: write header with magic + sample_rate, then length-prefixed frames
+    // Writer: write header with magic + version + sample_rate + channels, then length-prefixed frames
     let mut out = io::stdout();
     // magic
     let _ = out.write_all(b"SRAT");
+    let _ = out.write_all(&[PROTOCOL_VERSION]);
     let _ = out.write_all(&sample_rate.to_le_bytes());
+    let _ = out.write_all(&(out_channels as u16).to_le_bytes());
     let _ = out.flush();
 
-    for frame in rx {
+    while !SHUTDOWN.load(Ordering::Relaxed) {
+        let frame = match rx.recv_timeout(SHUTDOWN_POLL) {
+            Ok(frame) => frame,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            // The stream dropped its sender: nothing more will arrive
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
         // write length (number of samples) as u32 LE
         let len = frame.len() as u32;
-        let _ = out.write_all(&len.to_le_bytes());
         // write samples as i16 little-endian
-        let mut buf: Vec<u8> = Vec::with_capacity((len as usize) * 2);
+        let mut buf: Vec<u8> = Vec::with_capacity(4 + (len as usize) * 2);
+        buf.extend_from_slice(&len.to_le_bytes());
         for s in frame {
             buf.extend_from_slice(&s.to_le_bytes());
         }
-        let _ = out.write_all(&buf);
-        let _ = out.flush();
+        // A broken pipe means the parent stopped reading: no point capturing on
+        if let Err(e) = out.write_all(&buf).and_then(|_| out.flush()) {
+            eprintln!("audio_worker: stdout closed ({}); exiting", e);
+            break;
+        }
     }
 
-    // Keep process alive while stream is active
-    loop {
-        std::thread::park();
+    // Release the mic before exiting
+    drop(stream);
+}
+
+/// Open at the requested rate and channel count if some supported config covers them,
+/// then at the requested rate alone; otherwise the default. The header reports
+/// whichever config is actually used.
+fn choose_config(
+    device: &cpal::Device,
+    default_config: SupportedStreamConfig,
+    rate: Option<u32>,
+    channels: u16,
+) -> SupportedStreamConfig {
+    let rate = rate.unwrap_or(default_config.sample_rate().0);
+    if rate == default_config.sample_rate().0 && channels == default_config.channels() {
+        return default_config;
+    }
+    let supported: Vec<_> = device.supported_input_configs().map(|c| c.collect()).unwrap_or_default();
+    let find = |channels: u16| {
+        supported
+            .iter()
+            .copied()
+            .find(|c| {
+                c.sample_format() == default_config.sample_format()
+                    && c.channels() == channels
+                    && (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&rate)
+            })
+            .map(|c| c.with_sample_rate(cpal::SampleRate(rate)))
+    };
+    find(channels).or_else(|| find(default_config.channels())).unwrap_or_else(|| {
+        eprintln!(
+            "{} Hz x{} not supported; using default {} Hz x{}",
+            rate,
+            channels,
+            default_config.sample_rate().0,
+            default_config.channels()
+        );
+        default_config
+    })
+}
+
+/// Reduce `channels`-channel frames to `out_channels`: averaged down to mono like the
+/// app's `audio::meter::remix`, or the first `out_channels` kept
+fn remix(samples: Vec<i16>, channels: usize, out_channels: usize) -> Vec<i16> {
+    if channels == out_channels {
+        return samples;
+    }
+    if out_channels == 1 {
+        return samples
+            .chunks_exact(channels)
+            .map(|frame| {
+                let sum: i32 = frame.iter().map(|s| *s as i32).sum();
+                (sum / channels as i32) as i16
+            })
+            .collect();
     }
+    samples
+        .chunks_exact(channels)
+        .flat_map(|frame| frame[..out_channels].iter().copied())
+        .collect()
 }
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 🔑 A stored API key; `Debug` never shows it
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ApiKey(pub String);
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
+
+/// ⚙️ Persisted user settings (`~/.config/heard_it/config.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Last input device a recording successfully started on
+    pub preferred_device: Option<String>,
+    /// Treat a missing `audio_worker` binary as an error instead of silently
+    /// falling back to in-process capture
+    pub require_worker: bool,
+    /// How many times a crashed `audio_worker` is respawned per session
+    pub worker_max_respawns: u32,
+    /// How long to wait for the worker's header before falling back to in-process capture
+    pub worker_header_timeout_ms: u64,
+    /// Largest in-memory buffer `transcribe_bytes` accepts
+    pub max_upload_bytes: u64,
+    /// Meter only every Nth frame of each callback buffer (1 = every frame)
+    pub meter_decimation: usize,
+    /// Emit `transcript` as a bare string instead of a structured payload
+    pub legacy_transcript_event: bool,
+    /// How long capture continues after `stop_recording` (0 = stop immediately)
+    pub post_roll_ms: u64,
+    /// cpal host to capture from (e.g. `Alsa`); `None` prefers PulseAudio when available
+    pub audio_host: Option<String>,
+    /// Where history is saved instead of the app data directory (set via `set_history_dir`)
+    pub history_dir: Option<String>,
+    /// GGML model file for the local Whisper backend (e.g. `ggml-base.en.bin`)
+    pub whisper_model_path: Option<String>,
+    /// Deepgram API key set from the app; takes precedence over `DEEPGRAM_API_KEY`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deepgram_api_key: Option<ApiKey>,
+    /// OpenAI API key for the `openai` file backend; takes precedence over `OPENAI_API_KEY`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openai_api_key: Option<ApiKey>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            preferred_device: None,
+            require_worker: false,
+            worker_max_respawns: 3,
+            worker_header_timeout_ms: 3000,
+            max_upload_bytes: 200 * 1024 * 1024,
+            meter_decimation: 1,
+            legacy_transcript_event: false,
+            post_roll_ms: 300,
+            audio_host: None,
+            history_dir: None,
+            whisper_model_path: None,
+            deepgram_api_key: None,
+            openai_api_key: None,
+        }
+    }
+}
+
+/// Resolve the config file location ($XDG_CONFIG_HOME, then $HOME/.config)
+fn config_path() -> Option<PathBuf> {
+    let base = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(x) if !x.is_empty() => PathBuf::from(x),
+        _ => PathBuf::from(std::env::var("HOME").ok()?).join(".config"),
+    };
+    Some(base.join("heard_it").join("config.json"))
+}
+
+/// Load settings, falling back to defaults when the file is missing or unreadable
+pub fn load() -> Settings {
+    let Some(path) = config_path() else { return Settings::default() };
+    match fs::read_to_string(&path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            eprintln!("⚠️ Ignoring invalid config {:?}: {}", path, e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+/// Write settings to disk
+pub fn save(settings: &Settings) -> Result<(), String> {
+    let path = config_path().ok_or("Could not resolve config directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    write_private(&path, content.as_bytes()).map_err(|e| format!("Failed to write config: {}", e))
+}
+
+/// Write a file only the current user can read (0600 on Unix): it may hold the API key
+fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        opts.mode(0o600);
+        // `mode` only applies on creation; tighten files written by older versions too
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    opts.open(path)?.write_all(content)
+}
+
+/// Load, modify and save settings in one step
+pub fn update<F: FnOnce(&mut Settings)>(f: F) -> Result<Settings, String> {
+    let mut settings = load();
+    f(&mut settings);
+    save(&settings)?;
+    Ok(settings)
+}
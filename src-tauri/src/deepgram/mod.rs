@@ -1,184 +1,887 @@
+use futures_util::future::BoxFuture;
 use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
 use serde_json::Value;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter};
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::Receiver;
 use tokio_tungstenite::{
     connect_async,
-    tungstenite::{Message, client::IntoClientRequest},
+    tungstenite::{Message, client::IntoClientRequest, handshake::client::Request, protocol::frame::coding::CloseCode},
 };
 
-struct Resampler {
-    in_rate: u32,
-    out_rate: u32,
-    step: f64,
-    pos: f64,
-    buffer: Vec<f32>,
+pub mod resampler;
+mod sequence;
+
+use resampler::Resampler;
+use sequence::{SendTracker, Timeline};
+
+use crate::analysis;
+use crate::backend::TranscriptionBackend;
+use crate::model::{
+    live_channel, speaker_turns, top_alternative, ChannelTranscript, FileTranscript, Paragraph, Segment, TranscriptEvent,
+    TranscriptWords,
+};
+use crate::options::{self, FileOptions, RecordingOptions};
+use crate::session;
+use crate::transcript;
+use crate::vad::{NoiseGate, VoiceActivity};
+
+/// ⚠️ Error object returned by Deepgram instead of results, emitted as `transcription_error`
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionError {
+    pub message: String,
+    pub request_id: Option<String>,
 }
 
-impl Resampler {
-    fn new(in_rate: u32, out_rate: u32) -> Self {
-        let step = in_rate as f64 / out_rate as f64;
-        Resampler { in_rate, out_rate, step, pos: 0.0, buffer: Vec::new() }
+impl TranscriptionError {
+    /// Detect an error payload (`error`, `err_msg`, or a WS `{"type": "Error"}` message)
+    pub fn from_response(json: &Value) -> Option<Self> {
+        let message = json["error"]
+            .as_str()
+            .or_else(|| json["err_msg"].as_str())
+            .or_else(|| {
+                if json["type"] == "Error" {
+                    json["description"].as_str().or_else(|| json["message"].as_str()).or(Some("Unknown error"))
+                } else {
+                    None
+                }
+            })?;
+        Some(TranscriptionError {
+            message: message.to_string(),
+            request_id: json["request_id"].as_str().map(String::from),
+        })
     }
+}
 
-    // Push input samples and return resampled i16 vector
-    fn push_and_resample(&mut self, input: &[i16]) -> Vec<i16> {
-        // append input (as f32)
-        for &s in input {
-            self.buffer.push(s as f32);
-        }
+pub const MISSING_API_KEY: &str = "Deepgram API key not configured";
 
-        let mut out: Vec<i16> = Vec::new();
+/// Where the active API key comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeySource {
+    /// Set from the app (`set_api_key`), stored in config.json
+    Config,
+    /// The `DEEPGRAM_API_KEY` environment variable (or `.env`)
+    Env,
+}
 
-        // Produce resampled output while we have at least two samples available
-        // at the current fractional position (pos) and pos+1.
-        loop {
-            // we need access to floor(pos) and floor(pos)+1
-            let pos_floor = self.pos.floor() as usize;
-            if pos_floor + 1 >= self.buffer.len() {
-                break;
-            }
+/// 🔑 Whether a key is configured, without revealing it
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyStatus {
+    pub configured: bool,
+    pub source: Option<ApiKeySource>,
+    /// Last four characters only, e.g. `****abcd`
+    pub masked: Option<String>,
+}
 
-            let frac = (self.pos - (pos_floor as f64)) as f32;
-            let s0 = self.buffer[pos_floor];
-            let s1 = self.buffer[pos_floor + 1];
-            let sample_f = s0 * (1.0 - frac) + s1 * frac;
-
-            // clamp to i16
-            let sample_i16 = if sample_f.is_nan() {
-                0i16
-            } else {
-                let v = sample_f.round() as i64;
-                if v > i16::MAX as i64 { i16::MAX } else if v < i16::MIN as i64 { i16::MIN } else { v as i16 }
-            };
-            out.push(sample_i16);
-
-            self.pos += self.step;
-        }
-
-        // Drop consumed input samples to keep buffer small. Remove floor(pos) samples
-        // from the front and subtract that count from pos.
-        let remove = self.pos.floor() as usize;
-        if remove > 0 {
-            if remove >= self.buffer.len() {
-                // If we've consumed everything, clear buffer and reset pos
-                self.buffer.clear();
-                self.pos = 0.0;
-            } else {
-                self.buffer.drain(0..remove);
-                self.pos -= remove as f64;
-            }
+fn lookup_api_key() -> Option<(String, ApiKeySource)> {
+    if let Some(key) = crate::config::load().deepgram_api_key {
+        if !key.0.trim().is_empty() {
+            return Some((key.0, ApiKeySource::Config));
         }
+    }
+    match std::env::var("DEEPGRAM_API_KEY") {
+        Ok(key) if !key.trim().is_empty() => Some((key, ApiKeySource::Env)),
+        _ => None,
+    }
+}
+
+/// 🔑 The Deepgram API key (config first, then `DEEPGRAM_API_KEY`), or an error when it isn't configured
+pub fn api_key() -> Result<String, String> {
+    lookup_api_key().map(|(key, _)| key).ok_or_else(|| MISSING_API_KEY.to_string())
+}
 
-        out
+/// 🔑 Describe the configured key for the UI
+pub fn api_key_status() -> ApiKeyStatus {
+    match lookup_api_key() {
+        Some((key, source)) => {
+            let chars: Vec<char> = key.chars().collect();
+            let tail: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+            ApiKeyStatus { configured: true, source: Some(source), masked: Some(format!("****{}", tail)) }
+        }
+        None => ApiKeyStatus { configured: false, source: None, masked: None },
     }
 }
 
-pub async fn stream_to_deepgram(
-    mut rx: UnboundedReceiver<Vec<i16>>,
-    app: AppHandle,
-    sample_rate: u32,
-) {
-    let api_key = std::env::var("DEEPGRAM_API_KEY")
-        .expect("DEEPGRAM_API_KEY not set");
+/// 📤 POST a complete audio buffer to Deepgram's prerecorded endpoint and return the JSON response
+///
+/// `params` are appended to the query string (e.g. `("multichannel", "true")`), followed
+/// by the user's `extra` passthrough parameters.
+pub async fn transcribe_prerecorded(
+    audio: Vec<u8>,
+    content_type: Option<&str>,
+    params: &[(&str, &str)],
+    extra: &[(String, String)],
+) -> Result<Value, String> {
+    let api_key = api_key()?;
+    options::validate_extra_params(extra)?;
+
+    let mut url = String::from("https://api.deepgram.com/v1/listen?punctuate=true");
+    for (k, v) in params {
+        url.push('&');
+        url.push_str(k);
+        url.push('=');
+        url.extend(url::form_urlencoded::byte_serialize(v.as_bytes()));
+    }
+    let mut managed = vec!["punctuate"];
+    managed.extend(params.iter().map(|(k, _)| *k));
+    options::append_extra_params(&mut url, extra, &managed);
+
+    let client = reqwest::Client::new();
+    let mut request = client.post(url).header("Authorization", format!("Token {}", api_key));
+    // Without a Content-Type Deepgram detects the container itself
+    if let Some(content_type) = content_type {
+        request = request.header("Content-Type", content_type);
+    }
+    let response = request
+        .body(audio)
+        .send()
+        .await
+        .map_err(|e| format!("HTTP error: {}", e))?;
 
-    // If we will resample to 16000, tell Deepgram we'll be sending 16000 samples/sec.
-    let send_sample_rate = if sample_rate != 16000 { 16000 } else { sample_rate };
+    response.json().await.map_err(|e| format!("JSON parse error: {}", e))
+}
+
+/// 📋 Summarize `text` with Deepgram's text intelligence endpoint (`/v1/read?summarize=v2`)
+pub async fn summarize_text(text: &str, language: &str) -> Result<String, String> {
+    let api_key = api_key()?;
     let url = format!(
-        "wss://api.deepgram.com/v1/listen?encoding=linear16&sample_rate={}&punctuate=true",
-        send_sample_rate
+        "https://api.deepgram.com/v1/read?summarize=v2&language={}",
+        url::form_urlencoded::byte_serialize(language.as_bytes()).collect::<String>()
     );
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("Authorization", format!("Token {}", api_key))
+        .json(&serde_json::json!({ "text": text }))
+        .send()
+        .await
+        .map_err(|e| format!("HTTP error: {}", e))?;
+    let json: Value = response.json().await.map_err(|e| format!("JSON parse error: {}", e))?;
 
-    let mut request = url.into_client_request().unwrap();
+    if let Some(err) = TranscriptionError::from_response(&json) {
+        return Err(err.message);
+    }
+    json["results"]["summary"]["text"]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "Deepgram returned no summary".to_string())
+}
 
+/// Query parameters `streaming_url` sets itself
+const STREAMING_MANAGED_PARAMS: &[&str] =
+    &[
+        "encoding", "sample_rate", "model", "language", "punctuate", "smart_format", "interim_results", "diarize",
+        "keywords", "endpointing", "utterance_end_ms", "profanity_filter", "redact", "channels", "multichannel",
+    ];
+
+/// 🔗 Live-streaming endpoint for `options`, sending linear16 at `send_sample_rate`.
+/// More than one channel (interleaved) is transcribed per channel.
+fn streaming_url(options: &RecordingOptions, send_sample_rate: u32, channels: u16) -> String {
+    let mut url = format!(
+        "wss://api.deepgram.com/v1/listen?encoding=linear16&sample_rate={}&punctuate={}&smart_format={}&interim_results={}",
+        send_sample_rate, options.punctuate, options.smart_format, options.interim_results
+    );
+    if channels > 1 {
+        url.push_str(&format!("&channels={}&multichannel=true", channels));
+    }
+    if options.diarize {
+        url.push_str("&diarize=true");
+    }
+    if options.profanity_filter {
+        url.push_str("&profanity_filter=true");
+    }
+    for category in &options.redact {
+        url.push_str("&redact=");
+        url.push_str(category);
+    }
+    if let Some(ms) = options.endpointing {
+        url.push_str(&format!("&endpointing={}", ms));
+    }
+    if let Some(ms) = options.utterance_end_ms {
+        url.push_str(&format!("&utterance_end_ms={}", ms));
+    }
+    for (key, value) in [("model", options.model()), ("language", options.language())] {
+        url.push('&');
+        url.push_str(key);
+        url.push('=');
+        url.extend(url::form_urlencoded::byte_serialize(value.as_bytes()));
+    }
+    // Explicit keywords/boosts, then freeform context → keyword boosts
+    for kw in options.keyword_params() {
+        url.push_str("&keywords=");
+        url.extend(url::form_urlencoded::byte_serialize(kw.as_bytes()));
+    }
+    options::append_extra_params(&mut url, &options.extra_params, STREAMING_MANAGED_PARAMS);
+    url
+}
+
+/// 🔗 Authenticated handshake request for the live endpoint; the single place the
+/// streaming URL and headers are put together
+fn build_request(options: &RecordingOptions, send_sample_rate: u32, channels: u16, api_key: &str) -> Result<Request, String> {
+    let mut request = streaming_url(options, send_sample_rate, channels)
+        .into_client_request()
+        .map_err(|e| e.to_string())?;
     request.headers_mut().insert(
         "Authorization",
-        format!("Token {}", api_key).parse().unwrap(),
+        format!("Token {}", api_key).parse().map_err(|_| "Invalid API key".to_string())?,
     );
+    Ok(request)
+}
+
+/// 🔌 Deepgram as a [`TranscriptionBackend`]
+pub struct DeepgramBackend {
+    api_key: String,
+}
+
+impl DeepgramBackend {
+    /// Fails when no API key is configured
+    pub fn new() -> Result<Self, String> {
+        Ok(DeepgramBackend { api_key: api_key()? })
+    }
+}
+
+impl TranscriptionBackend for DeepgramBackend {
+    fn name(&self) -> &'static str {
+        "deepgram"
+    }
+
+    fn stream(
+        &self,
+        rx: Receiver<Vec<i16>>,
+        app: AppHandle,
+        sample_rate: u32,
+        channels: u16,
+        options: RecordingOptions,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(stream_to_deepgram(rx, app, sample_rate, channels, options, self.api_key.clone()))
+    }
+
+    /// `smart_format` also asks for `paragraphs`, so long files come back readable
+    fn transcribe_file(
+        &self,
+        audio: Vec<u8>,
+        mime: Option<&'static str>,
+        options: &FileOptions,
+    ) -> BoxFuture<'static, Result<FileTranscript, String>> {
+        let mut params = vec![("utterances", "true")];
+        if options.smart_format {
+            params.extend([("smart_format", "true"), ("paragraphs", "true")]);
+        }
+        if options.summarize {
+            params.push(("summarize", "v2"));
+        }
+        if options.detect_language {
+            params.push(("detect_language", "true"));
+        }
+        Box::pin(async move {
+            let json = transcribe_prerecorded(audio, mime, &params, &[]).await?;
+            println!("📨 Deepgram JSON: {}", json);
+            if let Some(err) = TranscriptionError::from_response(&json) {
+                return Err(err.message);
+            }
+            Ok(FileTranscript::from_response(&json))
+        })
+    }
+}
+
+/// Upper bound for the whole `test_options` round trip
+const OPTIONS_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 🧪 Outcome of a dry-run connection with a given option set
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionsCheck {
+    pub ok: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// 🧪 Open a stream with `options`, send ~1s of silence and wait for Deepgram's first reply.
+///
+/// Rejected option combinations surface as a failed handshake, an error message or
+/// an abnormal close. Gives up after [`OPTIONS_TEST_TIMEOUT`].
+pub async fn test_options(options: RecordingOptions) -> OptionsCheck {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(OPTIONS_TEST_TIMEOUT, probe_options(&options)).await {
+        Ok(r) => r,
+        Err(_) => Err(format!("No response from Deepgram within {:?}", OPTIONS_TEST_TIMEOUT)),
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(()) => OptionsCheck { ok: true, error: None, elapsed_ms },
+        Err(e) => OptionsCheck { ok: false, error: Some(e), elapsed_ms },
+    }
+}
+
+async fn probe_options(options: &RecordingOptions) -> Result<(), String> {
+    options.validate()?;
+    let api_key = api_key()?;
+
+    let send_sample_rate = options.send_rate.unwrap_or(16000);
+    let channels: u16 = if options.multichannel { 2 } else { 1 };
+    let request = build_request(options, send_sample_rate, channels, &api_key)?;
+    let (mut ws, _) = connect_async(request).await.map_err(|e| format!("Connection rejected: {}", e))?;
+
+    // One second of silence, then ask Deepgram to flush so it answers even without speech
+    let silence = vec![0u8; send_sample_rate as usize * channels as usize * 2];
+    ws.send(Message::Binary(silence)).await.map_err(|e| e.to_string())?;
+    ws.send(Message::Text(r#"{"type":"CloseStream"}"#.into())).await.map_err(|e| e.to_string())?;
+
+    while let Some(msg) = ws.next().await {
+        match msg.map_err(|e| e.to_string())? {
+            Message::Text(text) => {
+                let json: Value = serde_json::from_str(&text).map_err(|e| format!("Unexpected reply: {}", e))?;
+                let _ = ws.close(None).await;
+                return match TranscriptionError::from_response(&json) {
+                    Some(err) => Err(err.message),
+                    None => Ok(()),
+                };
+            }
+            Message::Close(Some(frame)) if frame.code != CloseCode::Normal => {
+                return Err(format!("Closed by Deepgram ({}): {}", u16::from(frame.code), frame.reason));
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Err("Connection closed without a reply".into())
+}
+
+type WsStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Reconnect attempts after a dropped connection before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// First reconnect delay; doubles on every attempt up to `MAX_RECONNECT_DELAY`
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(8);
+/// Upper bound on a single connection attempt
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Audio kept while reconnecting, in seconds at the send rate; older audio is dropped
+const MAX_RECONNECT_BUFFER_SECS: usize = 30;
+
+/// Emit `deepgram_status` ("connected" / "reconnecting" / "failed")
+fn emit_status(app: &AppHandle, status: &str, attempt: u32) {
+    let _ = app.emit("deepgram_status", serde_json::json!({ "status": status, "attempt": attempt }));
+}
+
+/// 🌐 Open the streaming socket; `request` is a `build_request` result, whose error is passed through
+async fn connect(request: Result<Request, String>) -> Result<WsStream, String> {
+    let request = request?;
+    match tokio::time::timeout(CONNECT_TIMEOUT, connect_async(request)).await {
+        Ok(Ok((ws, _))) => Ok(ws),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("Connection timed out after {:?}", CONNECT_TIMEOUT)),
+    }
+}
+
+/// Resample a captured (interleaved) chunk to the send rate; `resamplers` holds one
+/// resampler per channel, or none when the rates already match
+fn prepare_chunk(app: &AppHandle, resamplers: &mut [Resampler], chunk: Vec<i16>) -> Vec<i16> {
+    // The WAV copy (if any) gets the device-rate audio, before resampling
+    crate::recorder::write(&chunk);
+    if resamplers.is_empty() {
+        println!("🔁 Forwarding {} samples (no resample)", chunk.len());
+        return chunk;
+    }
+
+    let channels = resamplers.len();
+    let per_channel: Vec<Vec<i16>> = resamplers
+        .iter_mut()
+        .enumerate()
+        .map(|(c, res)| {
+            let samples: Vec<i16> = chunk.iter().skip(c).step_by(channels).copied().collect();
+            res.push_and_resample(&samples)
+        })
+        .collect();
+    // Every channel sees the same input length, so the resamplers stay in step
+    let dropped = resamplers[0].take_overrun();
+    for res in &mut resamplers[1..] {
+        res.take_overrun();
+    }
+    if dropped > 0 {
+        let _ = app.emit("resampler_overrun", dropped);
+    }
+
+    let frames = per_channel.iter().map(Vec::len).min().unwrap_or(0);
+    let v: Vec<i16> = (0..frames).flat_map(|i| per_channel.iter().map(move |c| c[i])).collect();
+    println!("🔁 Resampled {} -> {} samples", chunk.len(), v.len());
+    v
+}
+
+/// Send a `KeepAlive` after this long without audio (Deepgram closes idle sockets after ~10s)
+const KEEPALIVE_AFTER: Duration = Duration::from_secs(8);
+
+/// Everything `reconnect` needs to keep capturing while the socket is down
+struct StreamState {
+    resamplers: Vec<Resampler>,
+    /// Interleaved channels in `send_buf`
+    channels: usize,
+    send_buf: Vec<i16>,
+    tracker: SendTracker,
+    /// Maps the current connection's timestamps onto the session
+    timeline: Timeline,
+    /// Speech boundaries on the session time base, over everything captured
+    vad: VoiceActivity,
+    /// Set when `silence_threshold` is on
+    gate: Option<NoiseGate>,
+    /// Last batch the gate held back; sent ahead of the next loud one so the start
+    /// of the first word isn't lost
+    held: Option<Vec<i16>>,
+    send_sample_rate: u32,
+}
+
+/// How [`reconnect`] ended
+enum Reconnect {
+    Connected(Box<WsStream>),
+    /// The recording stopped while waiting for the next attempt
+    Stopped,
+    /// Every attempt failed
+    GaveUp,
+}
+
+/// 🔁 Reconnect with exponential backoff, buffering captured audio (capped) meanwhile.
+async fn reconnect(
+    request: impl Fn() -> Result<Request, String>,
+    rx: &mut Receiver<Vec<i16>>,
+    app: &AppHandle,
+    state: &mut StreamState,
+    max_buffer: usize,
+) -> Reconnect {
+    for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+        let delay = (RECONNECT_BASE_DELAY * 2u32.pow(attempt - 1)).min(MAX_RECONNECT_DELAY);
+        println!("🔁 Reconnecting to Deepgram in {:?} (attempt {}/{})", delay, attempt, MAX_RECONNECT_ATTEMPTS);
+        emit_status(app, "reconnecting", attempt);
+
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                chunk = rx.recv() => {
+                    let Some(chunk) = chunk else { return Reconnect::Stopped };
+                    let out = prepare_chunk(app, &mut state.resamplers, chunk);
+                    state.send_buf.extend_from_slice(&out);
+                    if state.send_buf.len() > max_buffer {
+                        // Whole frames only: both lengths are multiples of the channel count
+                        let excess = state.send_buf.len() - max_buffer;
+                        state.send_buf.drain(..excess);
+                        let gap = state.tracker.skip(excess / state.channels);
+                        eprintln!("⚠️ Reconnect buffer full: dropped {:.2}s-{:.2}s", gap.from_secs, gap.to_secs);
+                        let _ = app.emit("audio_gap", gap);
+                    }
+                }
+            }
+        }
+
+        match connect(request()).await {
+            Ok(ws) => return Reconnect::Connected(Box::new(ws)),
+            Err(e) => eprintln!("❌ Reconnect attempt {} failed: {}", attempt, e),
+        }
+    }
+    Reconnect::GaveUp
+}
+
+/// 🎧 Stream captured audio to Deepgram until `rx` closes; `api_key` comes from `api_key()`.
+///
+/// Frames on `rx` hold `channels` interleaved channels at `sample_rate`.
+pub async fn stream_to_deepgram(
+    mut rx: Receiver<Vec<i16>>,
+    app: AppHandle,
+    sample_rate: u32,
+    channels: u16,
+    options: RecordingOptions,
+    api_key: String,
+) {
+    // Send at the requested rate (16000 by default), resampling only if the device differs.
+    let send_sample_rate = options.send_rate.unwrap_or(16000);
+    let channels = channels.max(1);
+    let request = || build_request(&options, send_sample_rate, channels, &api_key);
+    let channels = channels as usize;
 
     println!("🌐 Connecting to Deepgram…");
-    let (mut ws, _) = connect_async(request).await.expect("WS failed");
+    let mut ws = match connect(request()).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            eprintln!("❌ Failed to connect to Deepgram: {}", e);
+            emit_status(&app, "failed", 0);
+            return;
+        }
+    };
     println!("✅ Connected to Deepgram");
+    emit_status(&app, "connected", 0);
 
-    // Prepare resampler (only used if we need to convert device rate -> send_sample_rate)
-    let mut maybe_resampler = if sample_rate != send_sample_rate {
-        Some(Resampler::new(sample_rate, send_sample_rate))
-    } else {
-        None
+    let mut state = StreamState {
+        // One resampler per channel, only if the device rate differs from send_sample_rate.
+        // It carries over reconnects: the captured audio itself is continuous.
+        resamplers: if sample_rate != send_sample_rate {
+            (0..channels).map(|_| Resampler::new(sample_rate, send_sample_rate)).collect()
+        } else {
+            Vec::new()
+        },
+        channels,
+        // Batches small frames into ~250ms sends; owned by this task, so every session starts empty
+        send_buf: Vec::new(),
+        // What was sent vs. acknowledged, so audio lost on a dropped connection can be reported
+        tracker: SendTracker::new(send_sample_rate),
+        timeline: Timeline::new(send_sample_rate, 0.0),
+        vad: VoiceActivity::new(send_sample_rate),
+        gate: (options.silence_threshold > 0.0).then(|| NoiseGate::new(options.silence_threshold, send_sample_rate)),
+        held: None,
+        send_sample_rate,
     };
+    let max_reconnect_buffer = send_sample_rate as usize * MAX_RECONNECT_BUFFER_SECS * channels;
+    // Read once: results arrive several times a second
+    let legacy_event = crate::config::load().legacy_transcript_event;
+
+    // threshold: ~250ms worth of frames at send_sample_rate (all channels)
+    let threshold_ms = 250f32;
+    let threshold_samples = ((send_sample_rate as f32) * (threshold_ms / 1000.0)).max(800.0) as usize * channels;
+
+    // Periodic live speaking-rate estimate over the trailing window
+    let mut rate_tick = tokio::time::interval(Duration::from_secs(5));
+
+    // Liveness signal for the UI: stops if this task dies
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(1));
+    let mut heartbeat_seq: u64 = 0;
+
+    // Fires only after KEEPALIVE_AFTER without audio (long silences, gated or paused
+    // audio); every real send resets it
+    let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + KEEPALIVE_AFTER, KEEPALIVE_AFTER);
 
     loop {
         tokio::select! {
-            Some(chunk) = rx.recv() => {
-                // Resample if needed and accumulate into a send buffer. We batch
-                // small frames into larger chunks (~250ms) before sending to Deepgram.
-                let out_vec: Vec<i16> = if let Some(res) = maybe_resampler.as_mut() {
-                    let v = res.push_and_resample(&chunk);
-                    println!("🔁 Resampled {} -> {} samples", chunk.len(), v.len());
-                    v
-                } else {
-                    println!("🔁 Forwarding {} samples (no resample)", chunk.len());
-                    chunk
-                };
+            _ = keepalive.tick() => {
+                println!("💓 No audio for {:?}: sending KeepAlive", KEEPALIVE_AFTER);
+                if let Err(e) = ws.send(Message::Text(r#"{"type":"KeepAlive"}"#.into())).await {
+                    eprintln!("⚠️ KeepAlive failed: {}", e);
+                }
+            }
+
+            _ = heartbeat.tick() => {
+                if session::state().is_active() {
+                    heartbeat_seq += 1;
+                    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+                    let _ = app.emit("recording_heartbeat", serde_json::json!({ "seq": heartbeat_seq, "ts": ts }));
+                }
+            }
 
-                // threshold: ~250ms worth of samples at send_sample_rate
-                let threshold_ms = 250f32;
-                let threshold_samples = ((send_sample_rate as f32) * (threshold_ms / 1000.0)).max(800.0) as usize;
-
-                // send buffer stored in outer scope local variable (create when first used)
-                static mut SEND_BUF_PTR: *mut Vec<i16> = std::ptr::null_mut();
-                // Safety: we mutate only within this single async task; use lazy init
-                let send_buf = unsafe {
-                    if SEND_BUF_PTR.is_null() {
-                        let b: Box<Vec<i16>> = Box::new(Vec::new());
-                        SEND_BUF_PTR = Box::into_raw(b);
+            _ = rate_tick.tick() => {
+                let words = transcript::words();
+                if !words.is_empty() {
+                    let wpm = analysis::live_wpm(&words, analysis::LIVE_WINDOW_SECS);
+                    let _ = app.emit("speaking_rate_live", serde_json::json!({ "wpm": wpm }));
+                }
+            }
+
+            chunk = rx.recv() => {
+                let Some(chunk) = chunk else {
+                    // Recording stopped: send the tail, then let Deepgram finish up
+                    drop_held(&mut state);
+                    if !state.send_buf.is_empty() {
+                        let tail = std::mem::take(&mut state.send_buf);
+                        send_batch(&mut ws, &tail, &mut state).await;
                     }
-                    &mut *SEND_BUF_PTR
+                    close_stream(&mut ws, &app, &mut state.tracker, &state.timeline, legacy_event).await;
+                    break;
                 };
 
-                send_buf.extend_from_slice(&out_vec);
+                // Resample if needed and accumulate into a send buffer. We batch
+                // small frames into larger chunks (~250ms) before sending to Deepgram.
+                let out_vec = prepare_chunk(&app, &mut state.resamplers, chunk);
+                state.send_buf.extend_from_slice(&out_vec);
 
                 // While we have enough samples, send in threshold-sized chunks
-                while send_buf.len() >= threshold_samples {
-                    let mut to_send: Vec<i16> = send_buf.drain(0..threshold_samples).collect();
-                    let bytes = unsafe {
-                        std::slice::from_raw_parts(
-                            to_send.as_ptr() as *const u8,
-                            to_send.len() * 2,
-                        )
-                    };
-                    println!("📤 Sending {} bytes to Deepgram (sample_rate={})", bytes.len(), send_sample_rate);
-                    let _ = ws.send(Message::Binary(bytes.to_vec())).await;
+                while state.send_buf.len() >= threshold_samples {
+                    let to_send: Vec<i16> = state.send_buf.drain(0..threshold_samples).collect();
+                    if send_or_gate(&mut ws, to_send, &mut state).await {
+                        keepalive.reset();
+                    }
                 }
             }
 
             msg = ws.next() => {
                 // Handle websocket messages robustly to avoid macro-level panics
                 match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        println!("📨 Deepgram JSON: {}", text);
-                        if let Ok(json) = serde_json::from_str::<Value>(&text) {
-                            if let Some(transcript) = json["results"]["channels"][0]["alternatives"][0]["transcript"].as_str() {
-                                if !transcript.trim().is_empty() {
-                                    println!("📝 TRANSCRIPT: {}", transcript);
-                                    let _ = app.emit("transcript", transcript.to_string()).ok();
-                                }
-                            }
-                        }
-                    }
+                    Some(Ok(Message::Text(text))) => handle_message(&app, &text, &mut state.tracker, &state.timeline, legacy_event),
                     Some(Ok(_other)) => {
                         // ignore non-text frames
                     }
-                    Some(Err(e)) => {
-                        eprintln!("❌ Deepgram WS error: {}", e);
-                        break;
-                    }
-                    None => {
-                        println!("🔌 Deepgram websocket closed");
-                        break;
+                    Some(Err(_)) | None => {
+                        match msg {
+                            Some(Err(e)) => eprintln!("❌ Deepgram WS error: {}", e),
+                            _ => println!("🔌 Deepgram websocket closed"),
+                        }
+                        if !session::state().is_active() {
+                            break;
+                        }
+                        drop_held(&mut state);
+                        report_gap(&app, &state.tracker);
+                        state.tracker.reset_after_gap();
+
+                        match reconnect(request, &mut rx, &app, &mut state, max_reconnect_buffer).await {
+                            Reconnect::Connected(new_ws) => {
+                                ws = *new_ws;
+                                // The new connection's timestamps start at zero again
+                                let offset = state.tracker.sent_secs();
+                                state.timeline = Timeline::new(send_sample_rate, offset);
+                                println!("✅ Reconnected to Deepgram (audio offset {:.2}s)", offset);
+                                emit_status(&app, "connected", 0);
+                                keepalive.reset();
+                                while state.send_buf.len() >= threshold_samples {
+                                    let to_send: Vec<i16> = state.send_buf.drain(0..threshold_samples).collect();
+                                    send_or_gate(&mut ws, to_send, &mut state).await;
+                                }
+                            }
+                            Reconnect::Stopped => {
+                                // Nothing failed: the audio buffered meanwhile just never got sent
+                                if !state.send_buf.is_empty() {
+                                    let frames = state.send_buf.len() / state.channels;
+                                    state.send_buf.clear();
+                                    let gap = state.tracker.skip(frames);
+                                    println!("🛑 Recording stopped while reconnecting: {:.2}s-{:.2}s not sent", gap.from_secs, gap.to_secs);
+                                    let _ = app.emit("audio_gap", gap);
+                                }
+                                break;
+                            }
+                            Reconnect::GaveUp => {
+                                eprintln!("❌ Giving up on Deepgram connection");
+                                emit_status(&app, "failed", MAX_RECONNECT_ATTEMPTS);
+                                break;
+                            }
+                        }
                     }
                 }
             }
         }
     }
+
+    if let Some(span) = state.vad.finish() {
+        transcript::append_speech(span);
+    }
+}
+
+/// How long to wait for Deepgram's final results after `CloseStream`
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Mono view of an interleaved batch, for the VAD and the noise gate
+fn batch_mono(samples: &[i16], channels: usize) -> std::borrow::Cow<'_, [i16]> {
+    if channels > 1 {
+        crate::audio::meter::downmix_to_mono(samples, channels).into()
+    } else {
+        samples.into()
+    }
+}
+
+fn feed_vad(vad: &mut VoiceActivity, mono: &[i16]) {
+    for span in vad.push(mono) {
+        transcript::append_speech(span);
+    }
+}
+
+/// 🚪 Send a batch unless the noise gate is closed; returns whether anything was sent.
+///
+/// A gated batch is held back for one round (pre-roll) and then skipped: the gap
+/// tracker and the timeline still account for it, so later timestamps stay on the
+/// session clock.
+async fn send_or_gate<S>(ws: &mut S, batch: Vec<i16>, state: &mut StreamState) -> bool
+where
+    S: futures_util::Sink<Message> + Unpin,
+{
+    let Some(gate) = state.gate.as_mut() else {
+        send_batch(ws, &batch, state).await;
+        return true;
+    };
+    if gate.admit(&batch_mono(&batch, state.channels)) {
+        if let Some(prev) = state.held.take() {
+            send_batch(ws, &prev, state).await;
+        }
+        send_batch(ws, &batch, state).await;
+        true
+    } else {
+        if let Some(prev) = state.held.replace(batch) {
+            skip_batch(&prev, state);
+        }
+        false
+    }
+}
+
+/// Account for a batch the gate kept from Deepgram
+fn skip_batch(samples: &[i16], state: &mut StreamState) {
+    feed_vad(&mut state.vad, &batch_mono(samples, state.channels));
+    let frames = samples.len() / state.channels;
+    state.tracker.record_gated(frames);
+    state.timeline.record_skipped(frames);
+}
+
+/// Skip the pre-roll batch, if any (end of stream or connection lost)
+fn drop_held(state: &mut StreamState) {
+    if let Some(prev) = state.held.take() {
+        skip_batch(&prev, state);
+    }
+}
+
+/// Serialize samples as `linear16`: little-endian i16, whatever the host byte order
+fn linear16_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// 📤 Send one batch of linear16 samples, keeping the VAD, gap tracking and timeline in step
+async fn send_batch<S>(ws: &mut S, samples: &[i16], state: &mut StreamState)
+where
+    S: futures_util::Sink<Message> + Unpin,
+{
+    feed_vad(&mut state.vad, &batch_mono(samples, state.channels));
+    let bytes = linear16_bytes(samples);
+    let frames = samples.len() / state.channels;
+    let seq = state.tracker.record_sent(frames);
+    state.timeline.record_sent(frames);
+    println!("📤 Sending batch #{} ({} bytes) to Deepgram (sample_rate={})", seq, bytes.len(), state.send_sample_rate);
+    let _ = ws.send(Message::Binary(bytes)).await;
+}
+
+/// 🏁 Send `CloseStream` and process the final results until Deepgram closes
+/// the socket (or [`CLOSE_TIMEOUT`] passes)
+async fn close_stream<S>(ws: &mut S, app: &AppHandle, tracker: &mut SendTracker, timeline: &Timeline, legacy_event: bool)
+where
+    S: futures_util::Sink<Message> + futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    println!("🏁 Sending CloseStream");
+    if ws.send(Message::Text(r#"{"type":"CloseStream"}"#.into())).await.is_err() {
+        return;
+    }
+    let drain = async {
+        while let Some(Ok(msg)) = ws.next().await {
+            match msg {
+                Message::Text(text) => handle_message(app, &text, tracker, timeline, legacy_event),
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    };
+    if tokio::time::timeout(CLOSE_TIMEOUT, drain).await.is_err() {
+        eprintln!("⏱️ Deepgram did not close within {:?}", CLOSE_TIMEOUT);
+    }
+}
+
+/// Move a result's timestamps onto the session clock (results after a reconnect
+/// start at zero, and gated silence never reached Deepgram)
+fn shift_times(json: &mut Value, timeline: &Timeline) {
+    let shift = |v: &mut Value| {
+        if let Some(t) = v.as_f64() {
+            *v = timeline.to_session(t).into();
+        }
+    };
+    shift(&mut json["start"]);
+    if let Some(alternatives) = json["channel"]["alternatives"].as_array_mut() {
+        for alt in alternatives {
+            if let Some(words) = alt["words"].as_array_mut() {
+                for w in words {
+                    shift(&mut w["start"]);
+                    shift(&mut w["end"]);
+                }
+            }
+        }
+    }
+}
+
+/// 📨 Handle one text message from the streaming socket; `timeline` places the
+/// current connection's timestamps within the session
+fn handle_message(app: &AppHandle, text: &str, tracker: &mut SendTracker, timeline: &Timeline, legacy_event: bool) {
+    println!("📨 Deepgram JSON: {}", text);
+    let Ok(mut json) = serde_json::from_str::<Value>(text) else { return };
+    // Mapped as a whole: a gated stretch may lie between `start` and the end
+    let processed_end = match (json["start"].as_f64(), json["duration"].as_f64()) {
+        (Some(start), Some(duration)) => Some(timeline.to_session(start + duration)),
+        _ => None,
+    };
+    if json.get("start").is_some() {
+        shift_times(&mut json, timeline);
+    }
+
+    // Silence after the last word: the UI starts a new paragraph
+    if json["type"] == "UtteranceEnd" {
+        let last_word_end = json["last_word_end"].as_f64().map(|t| timeline.to_session(t));
+        println!("⏎ Utterance end at {:?}", last_word_end);
+        let _ = app.emit("utterance_end", serde_json::json!({ "last_word_end": last_word_end }));
+        return;
+    }
+
+    if let Some(err) = TranscriptionError::from_response(&json) {
+        eprintln!("❌ Deepgram error: {}", err.message);
+        let _ = app.emit("transcription_error", err);
+        return;
+    }
+    if let Some(end) = processed_end {
+        tracker.acknowledge(end);
+    }
+    let Some(mut event) = TranscriptEvent::from_result(&json) else { return };
+    event.t_start = session::elapsed_secs();
+    let mut words = TranscriptWords::from_result(&json);
+    // Set for multichannel streams (one message per channel)
+    let channel = live_channel(&json);
+    for word in &mut words.words {
+        word.channel = channel;
+    }
+
+    // Keep finalized results in the session store (replay, analysis, ...)
+    if event.is_final {
+        transcript::append_words(&words.words);
+        let turns = speaker_turns(&words.words);
+        if turns.is_empty() {
+            let start = event.start.unwrap_or(0.0);
+            transcript::append_segment(Segment {
+                start,
+                end: event.end.unwrap_or(start),
+                text: event.text.clone(),
+                speaker: None,
+                channel,
+            });
+        } else {
+            for turn in &turns {
+                transcript::append_segment(Segment {
+                    start: turn.start,
+                    end: turn.end,
+                    text: turn.text.clone(),
+                    speaker: Some(turn.speaker),
+                    channel,
+                });
+            }
+            let _ = app.emit("diarized_transcript", turns);
+        }
+        // Only present when the request asked for paragraphs
+        let paragraphs = Paragraph::parse_list(top_alternative(&json));
+        if !paragraphs.is_empty() {
+            let _ = app.emit("transcript_paragraphs", paragraphs);
+        }
+        if channel.is_some() {
+            for c in ChannelTranscript::from_live(&json) {
+                let _ = app.emit("channel_transcript", c);
+            }
+        }
+    }
+    transcript::emit(app, event, legacy_event);
+    let _ = app.emit("transcript_words", words);
+}
+
+/// Emit `audio_gap` for audio lost to a connection drop (not for a user-initiated stop)
+fn report_gap(app: &AppHandle, tracker: &SendTracker) {
+    if !session::state().is_active() {
+        return;
+    }
+    if let Some(gap) = tracker.unacknowledged() {
+        eprintln!("⚠️ Audio {:.2}s-{:.2}s was not transcribed", gap.from_secs, gap.to_secs);
+        let _ = app.emit("audio_gap", gap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear16_is_little_endian() {
+        assert_eq!(linear16_bytes(&[0x0102, -2, i16::MIN, i16::MAX]), vec![0x02, 0x01, 0xFE, 0xFF, 0x00, 0x80, 0xFF, 0x7F]);
+        assert!(linear16_bytes(&[]).is_empty());
+    }
 }
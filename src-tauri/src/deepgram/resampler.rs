@@ -0,0 +1,268 @@
+use std::f64::consts::PI;
+
+/// Upper bound on buffered input, in seconds of audio at the input rate.
+const MAX_BUFFER_SECS: usize = 5;
+
+/// Filter half-width in zero crossings of the (widest) sinc; more = sharper cutoff.
+const ZERO_CROSSINGS: f64 = 16.0;
+/// Passband edge as a fraction of the lower Nyquist frequency, leaving room for the
+/// transition band below it so nothing folds back.
+const CUTOFF: f64 = 0.9;
+
+/// 🔁 Streaming band-limited resampler (Blackman-windowed sinc FIR).
+///
+/// When downsampling, the sinc is stretched so its cutoff sits below the output
+/// Nyquist frequency, which removes content that would otherwise alias.
+pub struct Resampler {
+    in_rate: u32,
+    out_rate: u32,
+    /// Position of the next output sample, in input samples from the buffer start:
+    /// `pos + frac / out_rate`. Kept as an exact fraction so long sessions don't drift.
+    pos: usize,
+    frac: u64,
+    /// Input history plus lookahead; starts with `half_width` zeros so output
+    /// sample 0 lines up with input sample 0
+    buffer: Vec<f32>,
+    max_buffer: usize,
+    overrun: usize,
+    /// Cutoff relative to the input Nyquist frequency (≤ `CUTOFF`)
+    cutoff: f64,
+    /// Kernel half-width in input samples
+    half_width: usize,
+}
+
+impl Resampler {
+    pub fn new(in_rate: u32, out_rate: u32) -> Self {
+        let cutoff = CUTOFF * (out_rate as f64 / in_rate as f64).min(1.0);
+        let half_width = (ZERO_CROSSINGS / cutoff).ceil() as usize;
+        let max_buffer = (in_rate as usize * MAX_BUFFER_SECS).max(2) + half_width;
+        Resampler {
+            in_rate,
+            out_rate,
+            pos: half_width,
+            frac: 0,
+            buffer: vec![0.0; half_width],
+            max_buffer,
+            overrun: 0,
+            cutoff,
+            half_width,
+        }
+    }
+
+    /// Number of input samples dropped by the buffer guard since the last call.
+    pub fn take_overrun(&mut self) -> usize {
+        std::mem::take(&mut self.overrun)
+    }
+
+    /// Filter weight for an input sample `d` input samples away from the output position
+    fn weight(&self, d: f64) -> f64 {
+        let u = d / self.half_width as f64;
+        if u.abs() >= 1.0 {
+            return 0.0;
+        }
+        let x = self.cutoff * d;
+        let sinc = if x.abs() < 1e-9 { 1.0 } else { (PI * x).sin() / (PI * x) };
+        let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+        sinc * window
+    }
+
+    /// Interpolate the output sample at the current position (needs `half_width` samples either side)
+    fn sample_at_pos(&self) -> f32 {
+        let pos = self.pos as f64 + self.frac as f64 / self.out_rate as f64;
+        let first = self.pos + 1 - self.half_width;
+        let last = self.pos + self.half_width;
+        let mut acc = 0.0;
+        let mut norm = 0.0;
+        for k in first..=last {
+            let w = self.weight(pos - k as f64);
+            acc += w * self.buffer[k] as f64;
+            norm += w;
+        }
+        // Normalising by the weight sum keeps unity gain at DC for every fractional phase
+        if norm.abs() > f64::EPSILON { (acc / norm) as f32 } else { 0.0 }
+    }
+
+    /// Step to the next output position (`in_rate / out_rate` input samples on)
+    fn advance(&mut self) {
+        self.frac += self.in_rate as u64;
+        let out_rate = self.out_rate as u64;
+        self.pos += (self.frac / out_rate) as usize;
+        self.frac %= out_rate;
+    }
+
+    // Push input samples and return resampled i16 vector
+    pub fn push_and_resample(&mut self, input: &[i16]) -> Vec<i16> {
+        // Same rate: nothing to filter, and skipping it avoids rounding the samples
+        if self.in_rate == self.out_rate {
+            return input.to_vec();
+        }
+
+        // append input (as f32)
+        for &s in input {
+            self.buffer.push(s as f32);
+        }
+
+        // Guard against pathological growth: keep at most a few seconds of input,
+        // dropping the oldest samples.
+        if self.buffer.len() > self.max_buffer {
+            let excess = self.buffer.len() - self.max_buffer;
+            eprintln!(
+                "⚠️ Resampler buffer overrun ({} -> {} Hz): dropping {} samples",
+                self.in_rate, self.out_rate, excess
+            );
+            self.buffer.drain(0..excess);
+            self.pos = self.pos.saturating_sub(excess).max(self.half_width);
+            self.overrun += excess;
+        }
+
+        let mut out: Vec<i16> = Vec::new();
+
+        // Produce output while the kernel's lookahead is available
+        while self.pos + self.half_width < self.buffer.len() {
+            let sample_f = self.sample_at_pos();
+
+            // clamp to i16
+            let sample_i16 = if sample_f.is_nan() {
+                0i16
+            } else {
+                let v = sample_f.round() as i64;
+                if v > i16::MAX as i64 { i16::MAX } else if v < i16::MIN as i64 { i16::MIN } else { v as i16 }
+            };
+            out.push(sample_i16);
+
+            self.advance();
+        }
+
+        // Drop input that no future output can reach, keeping `half_width` samples of history
+        let keep_from = (self.pos + 1).saturating_sub(self.half_width);
+        let remove = keep_from.min(self.buffer.len());
+        if remove > 0 {
+            self.buffer.drain(0..remove);
+            self.pos -= remove;
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f64, rate: u32, secs: f64, amplitude: f64) -> Vec<i16> {
+        let n = (rate as f64 * secs) as usize;
+        (0..n)
+            .map(|i| (amplitude * (2.0 * PI * freq * i as f64 / rate as f64).sin()).round() as i16)
+            .collect()
+    }
+
+    fn rms(samples: &[i16]) -> f64 {
+        (samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+    }
+
+    /// Frequency from rising zero crossings (skipping filter warm-up at both ends)
+    fn estimate_freq(samples: &[i16], rate: u32) -> f64 {
+        let crossings: Vec<usize> = samples
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] < 0 && w[1] >= 0)
+            .map(|(i, _)| i)
+            .collect();
+        let (first, last) = (crossings[1], crossings[crossings.len() - 2]);
+        (crossings.len() - 3) as f64 * rate as f64 / (last - first) as f64
+    }
+
+    #[test]
+    fn buffer_is_bounded_when_input_outruns_consumption() {
+        let mut r = Resampler::new(48000, 16000);
+        let input = vec![1000i16; 48000 * 20];
+
+        let out = r.push_and_resample(&input);
+
+        assert_eq!(r.take_overrun(), 48000 * 15);
+        assert_eq!(r.take_overrun(), 0);
+        assert!(r.buffer.len() <= r.max_buffer);
+        // only the retained 5s were resampled
+        assert!(out.len() <= 16000 * 5 + 1);
+    }
+
+    #[test]
+    fn preserves_tone_frequency_and_level() {
+        let input = sine(1000.0, 48000, 1.0, 10000.0);
+        let mut r = Resampler::new(48000, 16000);
+        let out = r.push_and_resample(&input);
+
+        let freq = estimate_freq(&out, 16000);
+        assert!((freq - 1000.0).abs() < 5.0, "got {} Hz", freq);
+
+        let body = &out[1000..out.len() - 1000];
+        let ratio = rms(body) / rms(&input);
+        assert!((ratio - 1.0).abs() < 0.02, "level ratio {}", ratio);
+    }
+
+    #[test]
+    fn attenuates_content_above_output_nyquist() {
+        // 12 kHz can't be represented at 16 kHz; a naive resampler folds it to 4 kHz
+        let input = sine(12000.0, 48000, 1.0, 10000.0);
+        let mut r = Resampler::new(48000, 16000);
+        let out = r.push_and_resample(&input);
+
+        let body = &out[1000..out.len() - 1000];
+        let ratio = rms(body) / rms(&input);
+        assert!(ratio < 0.01, "only attenuated to {}", ratio);
+    }
+
+    #[test]
+    fn empty_input_produces_nothing() {
+        let mut r = Resampler::new(48000, 16000);
+        assert!(r.push_and_resample(&[]).is_empty());
+        let state = (r.buffer.len(), r.pos, r.frac);
+        assert!(r.push_and_resample(&[]).is_empty());
+        assert_eq!((r.buffer.len(), r.pos, r.frac), state);
+    }
+
+    #[test]
+    fn single_sample_pushes_match_one_big_push() {
+        let input = sine(440.0, 44100, 0.25, 8000.0);
+        let whole = Resampler::new(44100, 16000).push_and_resample(&input);
+
+        let mut r = Resampler::new(44100, 16000);
+        let one_by_one: Vec<i16> = input.iter().flat_map(|s| r.push_and_resample(&[*s])).collect();
+
+        assert_eq!(whole, one_by_one);
+    }
+
+    #[test]
+    fn repeated_small_pushes_give_the_same_count() {
+        for (in_rate, out_rate) in [(44100, 16000), (48000, 16000), (16000, 44100), (22050, 16000)] {
+            let input = sine(300.0, in_rate, 0.5, 5000.0);
+            let whole = Resampler::new(in_rate, out_rate).push_and_resample(&input).len();
+
+            let mut r = Resampler::new(in_rate, out_rate);
+            let chunked: usize = input.chunks(333).map(|c| r.push_and_resample(c).len()).sum();
+
+            assert!(whole.abs_diff(chunked) <= 1, "{} -> {}: {} vs {}", in_rate, out_rate, whole, chunked);
+        }
+    }
+
+    #[test]
+    fn identical_rates_pass_through_unchanged() {
+        let input: Vec<i16> = vec![0, 1, -1, i16::MAX, i16::MIN, 1234];
+        let mut r = Resampler::new(16000, 16000);
+        assert_eq!(r.push_and_resample(&input), input);
+        assert_eq!(r.push_and_resample(&[]), Vec::<i16>::new());
+    }
+
+    #[test]
+    fn position_does_not_drift_over_long_runs() {
+        // One hour of output steps at 44.1k -> 16k; a float accumulator drifts here
+        let mut r = Resampler::new(44100, 16000);
+        let start = r.pos as u64;
+        let steps: u64 = 16000 * 3600;
+        for _ in 0..steps {
+            r.advance();
+        }
+        assert_eq!(r.pos as u64 - start, steps * 44100 / 16000);
+        assert_eq!(r.frac, steps * 44100 % 16000);
+    }
+}
@@ -0,0 +1,222 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Unacknowledged audio shorter than this isn't reported as a gap.
+const MIN_GAP_SECS: f64 = 0.05;
+
+/// ⚠️ Span of session audio that was sent but never transcribed, emitted as `audio_gap`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AudioGap {
+    pub from_secs: f64,
+    pub to_secs: f64,
+}
+
+/// 🧾 Sequence numbers and audio-time positions of the batches sent to Deepgram.
+///
+/// Results carry `start + duration`, which acknowledges everything up to that
+/// point; whatever was sent past the last acknowledgment when the socket drops is lost.
+pub struct SendTracker {
+    sample_rate: u32,
+    next_seq: u64,
+    sent_samples: u64,
+    acked_secs: f64,
+    /// (seq, audio end in seconds) of batches not yet acknowledged
+    in_flight: VecDeque<(u64, f64)>,
+}
+
+impl SendTracker {
+    pub fn new(sample_rate: u32) -> Self {
+        SendTracker { sample_rate: sample_rate.max(1), next_seq: 0, sent_samples: 0, acked_secs: 0.0, in_flight: VecDeque::new() }
+    }
+
+    /// Register a sent batch of `samples` samples; returns its sequence number
+    pub fn record_sent(&mut self, samples: usize) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.sent_samples += samples as u64;
+        self.in_flight.push_back((seq, self.sent_secs()));
+        seq
+    }
+
+    /// Audio time (seconds) covered by everything sent so far
+    pub fn sent_secs(&self) -> f64 {
+        self.sent_samples as f64 / self.sample_rate as f64
+    }
+
+    /// Mark audio up to `end_secs` as processed by Deepgram
+    pub fn acknowledge(&mut self, end_secs: f64) {
+        if end_secs <= self.acked_secs {
+            return;
+        }
+        self.acked_secs = end_secs;
+        while self.in_flight.front().is_some_and(|(_, end)| *end <= end_secs) {
+            self.in_flight.pop_front();
+        }
+        // Nothing left in flight: anything past the last batch was gated, not lost
+        if self.in_flight.is_empty() {
+            self.acked_secs = self.acked_secs.max(self.sent_secs());
+        }
+    }
+
+    /// Account for `samples` held back by the noise gate (silence, nothing to transcribe)
+    pub fn record_gated(&mut self, samples: usize) {
+        self.sent_samples += samples as u64;
+        if self.in_flight.is_empty() {
+            self.acked_secs = self.sent_secs();
+        }
+    }
+
+    /// Forget in-flight batches once their loss has been reported (connection dropped)
+    pub fn reset_after_gap(&mut self) {
+        self.acked_secs = self.sent_secs();
+        self.in_flight.clear();
+    }
+
+    /// Account for `samples` that were dropped instead of sent; returns the lost span
+    pub fn skip(&mut self, samples: usize) -> AudioGap {
+        let from_secs = self.sent_secs();
+        self.sent_samples += samples as u64;
+        self.acked_secs = self.sent_secs();
+        AudioGap { from_secs, to_secs: self.acked_secs }
+    }
+
+    /// Sent-but-unacknowledged audio, if any is worth reporting
+    pub fn unacknowledged(&self) -> Option<AudioGap> {
+        let to_secs = self.sent_secs();
+        if to_secs - self.acked_secs < MIN_GAP_SECS {
+            return None;
+        }
+        if let Some((seq, _)) = self.in_flight.front() {
+            println!("🧾 Batches #{}..#{} were never acknowledged", seq, self.next_seq - 1);
+        }
+        Some(AudioGap { from_secs: self.acked_secs, to_secs })
+    }
+}
+
+/// 🕰️ Maps a connection's timestamps onto the session timeline.
+///
+/// Deepgram's clock only advances with audio it receives, so it starts at the
+/// connection's offset within the session and falls behind by every stretch the
+/// noise gate held back.
+pub struct Timeline {
+    sample_rate: u32,
+    /// Samples sent on this connection (Deepgram's clock)
+    sent_samples: u64,
+    /// (connection time, offset to add from there on), in increasing connection time
+    anchors: Vec<(f64, f64)>,
+}
+
+impl Timeline {
+    pub fn new(sample_rate: u32, offset: f64) -> Self {
+        Timeline { sample_rate: sample_rate.max(1), sent_samples: 0, anchors: vec![(0.0, offset)] }
+    }
+
+    fn connection_secs(&self) -> f64 {
+        self.sent_samples as f64 / self.sample_rate as f64
+    }
+
+    pub fn record_sent(&mut self, samples: usize) {
+        self.sent_samples += samples as u64;
+    }
+
+    /// `samples` of session audio were not sent at the current connection time
+    pub fn record_skipped(&mut self, samples: usize) {
+        let at = self.connection_secs();
+        let skipped = samples as f64 / self.sample_rate as f64;
+        let last = self.anchors.last_mut().expect("timeline always has an anchor");
+        if last.0 == at {
+            last.1 += skipped;
+        } else {
+            let offset = last.1 + skipped;
+            self.anchors.push((at, offset));
+        }
+    }
+
+    /// Session time of a timestamp reported on this connection
+    pub fn to_session(&self, t: f64) -> f64 {
+        let offset = self.anchors.iter().rev().find(|(at, _)| *at <= t).unwrap_or(&self.anchors[0]).1;
+        t + offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    fn assert_gap(gap: Option<AudioGap>, from_secs: f64, to_secs: f64) {
+        let gap = gap.expect("expected a gap");
+        assert_close(gap.from_secs, from_secs);
+        assert_close(gap.to_secs, to_secs);
+    }
+
+    #[test]
+    fn acknowledging_past_in_flight_batches_clears_them() {
+        let mut tracker = SendTracker::new(1000);
+        assert_eq!(tracker.record_sent(500), 0);
+        assert_eq!(tracker.record_sent(500), 1);
+
+        tracker.acknowledge(0.5);
+        assert_gap(tracker.unacknowledged(), 0.5, 1.0);
+        // an older acknowledgment doesn't move it back
+        tracker.acknowledge(0.3);
+        assert_gap(tracker.unacknowledged(), 0.5, 1.0);
+
+        tracker.acknowledge(1.2);
+        assert!(tracker.unacknowledged().is_none());
+    }
+
+    #[test]
+    fn gated_audio_with_nothing_in_flight_is_not_a_gap() {
+        let mut tracker = SendTracker::new(1000);
+        tracker.record_gated(500);
+        assert_close(tracker.sent_secs(), 0.5);
+        assert!(tracker.unacknowledged().is_none());
+
+        // with a batch in flight, gated audio after it waits for that batch's acknowledgment
+        tracker.record_sent(500);
+        tracker.record_gated(500);
+        assert_gap(tracker.unacknowledged(), 0.5, 1.5);
+    }
+
+    #[test]
+    fn skip_returns_the_dropped_span_and_acknowledges_it() {
+        let mut tracker = SendTracker::new(1000);
+        tracker.record_sent(500);
+        let gap = tracker.skip(250);
+        assert_close(gap.from_secs, 0.5);
+        assert_close(gap.to_secs, 0.75);
+        assert_close(tracker.sent_secs(), 0.75);
+        assert!(tracker.unacknowledged().is_none());
+    }
+
+    #[test]
+    fn short_unacknowledged_audio_is_not_reported() {
+        let mut tracker = SendTracker::new(1000);
+        tracker.record_sent(40);
+        assert!(tracker.unacknowledged().is_none());
+        tracker.record_sent(20);
+        assert_gap(tracker.unacknowledged(), 0.0, 0.06);
+    }
+
+    #[test]
+    fn timeline_adds_each_skipped_stretch_from_where_it_happened() {
+        let mut timeline = Timeline::new(1000, 10.0);
+        assert_close(timeline.to_session(0.0), 10.0);
+
+        timeline.record_sent(1000);
+        timeline.record_skipped(500);
+        // skipped again before anything else was sent: same anchor
+        timeline.record_skipped(500);
+        timeline.record_sent(1000);
+        timeline.record_skipped(2000);
+
+        assert_close(timeline.to_session(0.5), 10.5);
+        assert_close(timeline.to_session(1.0), 12.0);
+        assert_close(timeline.to_session(1.5), 12.5);
+        assert_close(timeline.to_session(2.5), 15.5);
+    }
+}
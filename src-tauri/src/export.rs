@@ -0,0 +1,766 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use crate::model::{Segment, Word};
+use crate::vad::SpeechSpan;
+
+/// 💾 Outcome of a save-dialog export, emitted as `export_result`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportResult {
+    pub saved: bool,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ExportResult {
+    pub fn saved(path: &Path) -> Self {
+        ExportResult { saved: true, path: Some(path.to_string_lossy().to_string()), error: None }
+    }
+
+    pub fn failed(path: &Path, error: String) -> Self {
+        ExportResult { saved: false, path: Some(path.to_string_lossy().to_string()), error: Some(error) }
+    }
+
+    /// The user dismissed the dialog
+    pub fn cancelled() -> Self {
+        ExportResult { saved: false, path: None, error: None }
+    }
+}
+
+/// ⏱️ Timestamp layouts shared by the text and subtitle exports
+#[derive(Debug, Clone, Copy)]
+pub enum TimestampStyle {
+    /// `mm:ss` (minutes keep counting past an hour)
+    MinSec,
+    /// `hh:mm:ss,mmm`
+    Srt,
+    /// `hh:mm:ss.mmm`
+    Vtt,
+}
+
+/// Format a position in seconds; negative values clamp to zero.
+pub fn format_timestamp(secs: f64, style: TimestampStyle) -> String {
+    let total_ms = (secs.max(0.0) * 1000.0).round() as u64;
+    let h = total_ms / 3_600_000;
+    let m = total_ms / 60_000 % 60;
+    let s = total_ms / 1000 % 60;
+    let ms = total_ms % 1000;
+    match style {
+        TimestampStyle::MinSec => format!("{:02}:{:02}", total_ms / 60_000, s),
+        TimestampStyle::Srt => format!("{:02}:{:02}:{:02},{:03}", h, m, s, ms),
+        TimestampStyle::Vtt => format!("{:02}:{:02}:{:02}.{:03}", h, m, s, ms),
+    }
+}
+
+/// 📝 The Markdown document `export_md` writes (and `copy_to_clipboard` copies)
+pub fn markdown(body: &str) -> String {
+    format!("# Transcript\n\n{}", body)
+}
+
+/// 📝 Plain text with each segment on its own `[mm:ss]`-prefixed line
+pub fn timestamped_text(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for seg in segments {
+        let text = seg.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("[{}] {}\n", format_timestamp(seg.start, TimestampStyle::MinSec), text));
+    }
+    out
+}
+
+/// ↩️ Hard-wrap every line at word boundaries to at most `width` columns.
+///
+/// Existing line breaks are kept; words longer than `width` are split across lines.
+pub fn wrap_text(text: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut out = String::with_capacity(text.len() + text.len() / width);
+
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut col = 0;
+        for word in line.split_whitespace() {
+            let mut chars: Vec<char> = word.chars().collect();
+            if col > 0 && col + 1 + chars.len() <= width {
+                out.push(' ');
+                col += 1;
+            } else if col > 0 {
+                out.push('\n');
+                col = 0;
+            }
+            while chars.len() > width - col {
+                let rest = chars.split_off(width - col);
+                out.extend(chars);
+                out.push('\n');
+                chars = rest;
+                col = 0;
+            }
+            col += chars.len();
+            out.extend(chars);
+        }
+    }
+    out
+}
+
+/// Length given to each chunk when nothing better than the text is known.
+const NAIVE_CUE_SECS: f64 = 5.0;
+
+/// Longest caption cue: two subtitle lines of ~42 characters
+const MAX_CUE_CHARS: usize = 84;
+
+/// Abbreviations that are followed by a name or number, never by a new sentence
+const TITLE_ABBREVIATIONS: &[&str] = &[
+    "mr.", "mrs.", "ms.", "dr.", "prof.", "st.", "sr.", "jr.", "vs.", "no.", "fig.", "approx.", "e.g.", "i.e.", "cf.",
+];
+
+/// Whether `token` ends a sentence, given the token after it
+fn ends_sentence(token: &str, next: Option<&str>) -> bool {
+    let token = token.trim_end_matches(['"', '\'', ')', ']', '”', '’']);
+    if token.ends_with(['!', '?']) {
+        return true;
+    }
+    if !token.ends_with('.') {
+        return false;
+    }
+    let Some(next) = next else { return true };
+    let lower = token.to_lowercase();
+    if TITLE_ABBREVIATIONS.contains(&lower.as_str()) {
+        return false;
+    }
+    // An initial ("J. Smith")
+    if token.chars().count() == 2 && token.starts_with(char::is_alphabetic) {
+        return false;
+    }
+    // "3.14 p.m. and" carries on; "p.m. It" starts a new sentence
+    !next.starts_with(char::is_lowercase)
+}
+
+/// ✂️ Split text into sentences without breaking at abbreviations ("Dr.", "p.m.
+/// and") or decimals ("3.14"); line breaks always end a sentence.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut current: Vec<&str> = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            current.push(token);
+            if ends_sentence(token, tokens.get(i + 1).copied()) {
+                sentences.push(current.join(" "));
+                current.clear();
+            }
+        }
+        if !current.is_empty() {
+            sentences.push(current.join(" "));
+        }
+    }
+    sentences
+}
+
+/// Sentences, with those longer than [`MAX_CUE_CHARS`] broken at word boundaries
+fn cue_texts(text: &str) -> Vec<String> {
+    let mut cues = Vec::new();
+    for sentence in split_sentences(text) {
+        if sentence.chars().count() <= MAX_CUE_CHARS {
+            cues.push(sentence);
+            continue;
+        }
+        // `wrap_text` splits overlong words too, so every line fits
+        cues.extend(wrap_text(&sentence, MAX_CUE_CHARS).lines().map(str::to_string));
+    }
+    cues
+}
+
+/// 💬 Caption cues for subtitle exports when only the flat text is available.
+///
+/// With VAD speech spans, the words are spread over the spans in proportion to each
+/// span's length: a best-effort middle ground that follows the real pauses without
+/// per-word timings. Without spans, each sentence (split further past two subtitle
+/// lines) gets a fixed 5s slot.
+pub fn caption_cues(text: &str, speech: &[SpeechSpan]) -> Vec<Segment> {
+    let cue = |start: f64, end: f64, text: String| Segment { start, end, text, speaker: None, channel: None };
+
+    let total_secs: f64 = speech.iter().map(|s| (s.end - s.start).max(0.0)).sum();
+    if total_secs <= 0.0 {
+        return cue_texts(text)
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| cue(i as f64 * NAIVE_CUE_SECS, (i + 1) as f64 * NAIVE_CUE_SECS, p))
+            .collect();
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut cues = Vec::with_capacity(speech.len());
+    let mut taken = 0;
+    let mut elapsed = 0.0;
+    for span in speech {
+        elapsed += (span.end - span.start).max(0.0);
+        let until = ((elapsed / total_secs) * words.len() as f64).round() as usize;
+        let until = until.clamp(taken, words.len());
+        if until > taken {
+            cues.push(cue(span.start, span.end, words[taken..until].join(" ")));
+            taken = until;
+        }
+    }
+    cues
+}
+
+/// 🎞️ Readability limits for SRT/VTT cues (defaults follow common subtitle guidelines)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct CueLimits {
+    /// Characters per subtitle line
+    pub max_line_chars: usize,
+    /// Lines per cue
+    pub max_lines: usize,
+    /// Longest time a cue stays on screen, in seconds
+    pub max_secs: f64,
+}
+
+impl Default for CueLimits {
+    fn default() -> Self {
+        CueLimits { max_line_chars: 42, max_lines: 2, max_secs: 6.0 }
+    }
+}
+
+/// ✂️ Split cues that break `limits` at word boundaries, wrapping each into lines.
+///
+/// A cue's time is shared out in proportion to the characters of each piece.
+pub fn limit_cues(cues: &[Segment], limits: CueLimits) -> Vec<Segment> {
+    let line_chars = limits.max_line_chars.max(1);
+    let max_lines = limits.max_lines.max(1);
+    let max_secs = if limits.max_secs > 0.0 { limits.max_secs } else { f64::INFINITY };
+
+    let mut out = Vec::new();
+    for cue in cues {
+        let wrapped = wrap_text(&cue.text.split_whitespace().collect::<Vec<_>>().join(" "), line_chars);
+        let lines: Vec<&str> = wrapped.lines().collect();
+        let mut pieces: Vec<String> = Vec::new();
+        for group in lines.chunks(max_lines) {
+            let piece = group.join(" ");
+            let share = piece.chars().count() as f64 / wrapped.chars().count().max(1) as f64;
+            let splits = ((cue.end - cue.start) * share / max_secs).ceil().max(1.0) as usize;
+            let words: Vec<&str> = piece.split_whitespace().collect();
+            let per_split = words.len().div_ceil(splits.min(words.len()).max(1));
+            pieces.extend(words.chunks(per_split.max(1)).map(|w| w.join(" ")));
+        }
+
+        let total_chars: usize = pieces.iter().map(|p| p.chars().count()).sum::<usize>().max(1);
+        let mut start = cue.start;
+        for piece in pieces {
+            let share = piece.chars().count() as f64 / total_chars as f64;
+            let end = start + (cue.end - cue.start) * share;
+            out.push(Segment {
+                start,
+                end,
+                text: wrap_text(&piece, line_chars),
+                speaker: cue.speaker,
+                channel: cue.channel,
+            });
+            start = end;
+        }
+    }
+    out
+}
+
+/// Schema version of [`TranscriptJson`]
+pub const TRANSCRIPT_JSON_VERSION: u32 = 1;
+
+/// 🧾 Stable structured transcript written by `export_json`
+///
+/// Field names are part of the schema: change them only together with a bump of
+/// `version` ([`TRANSCRIPT_JSON_VERSION`]).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TranscriptJson {
+    pub version: u32,
+    pub segments: Vec<JsonSegment>,
+}
+
+/// One timed segment of [`TranscriptJson`] (seconds from session start)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct JsonSegment {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<u32>,
+    pub channel: Option<usize>,
+    pub text: String,
+    pub words: Vec<JsonWord>,
+}
+
+/// One word of a [`JsonSegment`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct JsonWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    pub confidence: f64,
+    pub speaker: Option<u32>,
+}
+
+/// Words of `seg`: those starting inside its time range (and from its speaker, when diarized)
+fn segment_words<'a>(seg: &'a Segment, words: &'a [Word]) -> impl Iterator<Item = &'a Word> + 'a {
+    words
+        .iter()
+        .filter(|w| w.start >= seg.start && w.start <= seg.end)
+        .filter(|w| seg.speaker.is_none() || w.speaker == seg.speaker)
+}
+
+/// Attach each word to the segment whose time range contains its start
+pub fn transcript_json(segments: &[Segment], words: &[Word]) -> TranscriptJson {
+    let segments = segments
+        .iter()
+        .map(|seg| JsonSegment {
+            start: seg.start,
+            end: seg.end,
+            speaker: seg.speaker,
+            channel: seg.channel,
+            text: seg.text.trim().to_string(),
+            words: segment_words(seg, words)
+                .map(|w| JsonWord {
+                    word: w.word.clone(),
+                    start: w.start,
+                    end: w.end,
+                    confidence: w.confidence,
+                    speaker: w.speaker,
+                })
+                .collect(),
+        })
+        .collect();
+    TranscriptJson { version: TRANSCRIPT_JSON_VERSION, segments }
+}
+
+/// 📊 CSV with one row per segment: start_seconds, end_seconds, speaker, confidence, text.
+///
+/// Confidence is the mean of the segment's word confidences (empty without words).
+/// Without segments, `fallback_text` becomes a single row with only the text.
+pub fn csv(segments: &[Segment], words: &[Word], fallback_text: &str) -> Result<Vec<u8>, String> {
+    let mut writer = ::csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["start_seconds", "end_seconds", "speaker", "confidence", "text"])
+        .map_err(|e| e.to_string())?;
+
+    if segments.is_empty() {
+        writer
+            .write_record(["", "", "", "", fallback_text.trim()])
+            .map_err(|e| e.to_string())?;
+    }
+    for seg in segments {
+        let confidences: Vec<f64> = segment_words(seg, words).map(|w| w.confidence).collect();
+        let confidence = if confidences.is_empty() {
+            String::new()
+        } else {
+            format!("{:.3}", confidences.iter().sum::<f64>() / confidences.len() as f64)
+        };
+        writer
+            .write_record([
+                format!("{:.3}", seg.start),
+                format!("{:.3}", seg.end),
+                seg.speaker.map(|s| s.to_string()).unwrap_or_default(),
+                confidence,
+                seg.text.trim().to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// Gaps shorter than this are not worth an explicit empty cue.
+const MIN_GAP_SECS: f64 = 0.01;
+
+/// 🎞️ One cue of the web-player timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineCue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    pub speaker: Option<u32>,
+}
+
+/// 🎞️ Player-oriented transcript timeline covering `[0, duration]`
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    pub duration: f64,
+    pub cues: Vec<TimelineCue>,
+}
+
+/// Build a contiguous timeline: segments must be sorted and inside `[0, duration]`;
+/// silent gaps between them are filled with empty-text cues.
+pub fn timeline(segments: &[Segment], duration: f64) -> Result<Timeline, String> {
+    if !(duration.is_finite() && duration >= 0.0) {
+        return Err(format!("Invalid audio duration: {}", duration));
+    }
+
+    let mut cues = Vec::with_capacity(segments.len() * 2 + 1);
+    let mut cursor = 0.0;
+
+    for (i, seg) in segments.iter().enumerate() {
+        if !(seg.start >= 0.0 && seg.end >= seg.start && seg.end <= duration) {
+            return Err(format!(
+                "Segment {} ({:.3}-{:.3}) is outside [0, {:.3}]",
+                i, seg.start, seg.end, duration
+            ));
+        }
+        if seg.start < cursor {
+            return Err(format!("Segment {} starts before the previous one ends", i));
+        }
+
+        if seg.start - cursor > MIN_GAP_SECS {
+            cues.push(TimelineCue { start: cursor, end: seg.start, text: String::new(), speaker: None });
+        }
+        cues.push(TimelineCue {
+            start: seg.start,
+            end: seg.end,
+            text: seg.text.trim().to_string(),
+            speaker: seg.speaker,
+        });
+        cursor = seg.end;
+    }
+
+    if duration - cursor > MIN_GAP_SECS {
+        cues.push(TimelineCue { start: cursor, end: duration, text: String::new(), speaker: None });
+    }
+
+    Ok(Timeline { duration, cues })
+}
+
+/// 🎞️ Timeline of the transcript store's segments, which arrive in finalization
+/// order and overlap in diarized or multichannel sessions.
+///
+/// They are sorted by start and overlapping ones merged into one cue; when a merge
+/// mixes speakers, each part keeps a "Speaker N:" label. `duration` defaults to the
+/// latest segment end.
+pub fn session_timeline(mut segments: Vec<Segment>, duration: Option<f64>) -> Result<Timeline, String> {
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+    for seg in segments {
+        let Some(prev) = merged.last_mut().filter(|prev| seg.start < prev.end) else {
+            merged.push(seg);
+            continue;
+        };
+        if prev.speaker != seg.speaker {
+            if let Some(speaker) = prev.speaker.take() {
+                prev.text = speaker_labeled(Some(speaker), &prev.text);
+            }
+            prev.text = format!("{} {}", prev.text.trim(), speaker_labeled(seg.speaker, &seg.text));
+        } else {
+            prev.text = format!("{} {}", prev.text.trim(), seg.text.trim());
+        }
+        if prev.channel != seg.channel {
+            prev.channel = None;
+        }
+        prev.end = prev.end.max(seg.end);
+    }
+
+    let duration = duration.unwrap_or_else(|| latest_end(&merged));
+    timeline(&merged, duration)
+}
+
+/// End of the segment that ends last (not necessarily the last one)
+pub fn latest_end(segments: &[Segment]) -> f64 {
+    segments.iter().map(|s| s.end).fold(0.0, f64::max)
+}
+
+fn speaker_labeled(speaker: Option<u32>, text: &str) -> String {
+    match speaker {
+        Some(n) => format!("Speaker {}: {}", n, text.trim()),
+        None => text.trim().to_string(),
+    }
+}
+
+/// Escape text for inclusion in HTML body/attribute content.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 🌡️ Standalone HTML page with every word color-coded by confidence
+pub fn confidence_html(words: &[Word]) -> String {
+    let mut body = String::new();
+    for w in words {
+        let confidence = w.confidence.clamp(0.0, 1.0);
+        let class = if confidence >= 0.9 {
+            "high"
+        } else if confidence >= 0.7 {
+            "mid"
+        } else {
+            "low"
+        };
+        // fade out less reliable words a little on top of the color
+        let opacity = 0.45 + 0.55 * confidence;
+        body.push_str(&format!(
+            "<span class=\"w {}\" style=\"opacity:{:.2}\" title=\"{:.0}% @ {:.2}s\">{}</span>\n",
+            class,
+            opacity,
+            confidence * 100.0,
+            w.start,
+            escape_html(&w.word)
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Transcript confidence</title>\n<style>\n\
+body {{ font-family: sans-serif; line-height: 1.8; max-width: 50em; margin: 2em auto; }}\n\
+.w {{ padding: 0 2px; border-radius: 3px; }}\n\
+.high {{ background: #c8f7c5; }}\n\
+.mid {{ background: #ffe8a3; }}\n\
+.low {{ background: #f7b7b7; }}\n\
+</style>\n</head>\n<body>\n<h1>Transcript confidence</h1>\n<p>\n{}</p>\n</body>\n</html>\n",
+        body
+    )
+}
+
+/// 📄 One paragraph of a document export; `label` (e.g. "Speaker 1") is rendered bold
+pub struct DocParagraph {
+    pub label: Option<String>,
+    pub text: String,
+}
+
+/// Paragraphs for document exports: one per segment with speaker labels when the
+/// segments are diarized, otherwise one per non-empty line of the plain text
+pub fn doc_paragraphs(text: &str, segments: &[Segment]) -> Vec<DocParagraph> {
+    if segments.iter().any(|s| s.speaker.is_some()) {
+        return segments
+            .iter()
+            .filter(|s| !s.text.trim().is_empty())
+            .map(|s| DocParagraph {
+                label: s.speaker.map(|n| format!("Speaker {}", n)),
+                text: s.text.trim().to_string(),
+            })
+            .collect();
+    }
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| DocParagraph { label: None, text: l.to_string() })
+        .collect()
+}
+
+/// Escape text for XML element content
+fn escape_xml(text: &str) -> String {
+    escape_html(text).replace("&#39;", "&apos;")
+}
+
+fn docx_run(text: &str, bold: bool, half_points: Option<u32>) -> String {
+    let mut props = String::new();
+    if bold {
+        props.push_str("<w:b/>");
+    }
+    if let Some(size) = half_points {
+        props.push_str(&format!("<w:sz w:val=\"{}\"/>", size));
+    }
+    let props = if props.is_empty() { props } else { format!("<w:rPr>{}</w:rPr>", props) };
+    format!("<w:r>{}<w:t xml:space=\"preserve\">{}</w:t></w:r>", props, escape_xml(text))
+}
+
+const DOCX_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+const DOCX_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+/// 📝 Minimal Word document: a bold title followed by one paragraph each
+pub fn docx(title: &str, paragraphs: &[DocParagraph]) -> Result<Vec<u8>, String> {
+    let mut body = format!("<w:p>{}</w:p>", docx_run(title, true, Some(32)));
+    for p in paragraphs {
+        body.push_str("<w:p>");
+        if let Some(label) = &p.label {
+            body.push_str(&docx_run(&format!("{}: ", label), true, None));
+        }
+        body.push_str(&docx_run(&p.text, false, None));
+        body.push_str("</w:p>");
+    }
+    let document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{}</w:body></w:document>",
+        body
+    );
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in [
+        ("[Content_Types].xml", DOCX_CONTENT_TYPES),
+        ("_rels/.rels", DOCX_RELS),
+        ("word/document.xml", document.as_str()),
+    ] {
+        zip.start_file(name, options).map_err(|e| format!("Failed to build DOCX: {}", e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| format!("Failed to build DOCX: {}", e))?;
+    }
+    let cursor = zip.finish().map_err(|e| format!("Failed to build DOCX: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// `YYYY-MM-DD` (UTC) for a unix timestamp
+pub fn utc_date(unix_secs: u64) -> String {
+    // days-to-civil conversion (proleptic Gregorian calendar)
+    let z = (unix_secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Column width PDF body text is wrapped to when the caller doesn't choose one
+pub const PDF_DEFAULT_WRAP: usize = 85;
+
+const PDF_PAGE_W: f32 = 210.0;
+const PDF_PAGE_H: f32 = 297.0;
+const PDF_MARGIN: f32 = 20.0;
+const PDF_LINE_HEIGHT: f32 = 5.5;
+const PDF_BODY_PT: f32 = 11.0;
+
+/// 🧾 A4 PDF: "Transcript" title, wrapped and paginated body, export-date footer.
+///
+/// Uses the built-in Helvetica fonts, so characters outside Latin-1 don't render.
+pub fn pdf(text: &str, wrap_width: usize, exported_on: &str) -> Result<Vec<u8>, String> {
+    use printpdf::{BuiltinFont, Mm, PdfDocument};
+
+    let (doc, first_page, first_layer) = PdfDocument::new("Transcript", Mm(PDF_PAGE_W), Mm(PDF_PAGE_H), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+
+    let footer = format!("Exported {}", exported_on);
+    let mut layer = doc.get_page(first_page).get_layer(first_layer);
+    layer.use_text("Transcript", 18.0, Mm(PDF_MARGIN), Mm(PDF_PAGE_H - PDF_MARGIN), &bold);
+    let mut y = PDF_PAGE_H - PDF_MARGIN - 12.0;
+    let mut page_no = 1;
+
+    let wrapped = wrap_text(text, wrap_width);
+    for line in wrapped.lines() {
+        if y < PDF_MARGIN + 5.0 {
+            layer.use_text(format!("{} - page {}", footer, page_no), 8.0, Mm(PDF_MARGIN), Mm(12.0), &font);
+            let (page, new_layer) = doc.add_page(Mm(PDF_PAGE_W), Mm(PDF_PAGE_H), "Layer 1");
+            layer = doc.get_page(page).get_layer(new_layer);
+            y = PDF_PAGE_H - PDF_MARGIN;
+            page_no += 1;
+        }
+        layer.use_text(line, PDF_BODY_PT, Mm(PDF_MARGIN), Mm(y), &font);
+        y -= PDF_LINE_HEIGHT;
+    }
+    layer.use_text(format!("{} - page {}", footer, page_no), 8.0, Mm(PDF_MARGIN), Mm(12.0), &font);
+
+    doc.save_to_bytes().map_err(|e| format!("Failed to build PDF: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue_text(text: &str) -> Vec<String> {
+        caption_cues(text, &[]).into_iter().map(|c| c.text).collect()
+    }
+
+    #[test]
+    fn abbreviations_and_decimals_do_not_end_cues() {
+        assert_eq!(
+            cue_text("Dr. Smith arrived at 3.14 p.m. It was late."),
+            vec!["Dr. Smith arrived at 3.14 p.m.", "It was late."]
+        );
+    }
+
+    #[test]
+    fn sentence_ends_split_cues() {
+        assert_eq!(
+            cue_text("Is it on? Yes! Mr. J. Doe said so. then lowercase."),
+            vec!["Is it on?", "Yes!", "Mr. J. Doe said so. then lowercase."]
+        );
+    }
+
+    #[test]
+    fn cues_are_timed_in_order() {
+        let cues = caption_cues("One. Two.", &[]);
+        assert_eq!(cues.len(), 2);
+        assert_eq!((cues[0].start, cues[0].end), (0.0, NAIVE_CUE_SECS));
+        assert_eq!((cues[1].start, cues[1].end), (NAIVE_CUE_SECS, 2.0 * NAIVE_CUE_SECS));
+    }
+
+    #[test]
+    fn unpunctuated_text_is_split_by_length() {
+        let text = vec!["word"; 60].join(" ");
+        let cues = cue_text(&text);
+        assert!(cues.len() > 1);
+        assert!(cues.iter().all(|c| c.chars().count() <= MAX_CUE_CHARS));
+        assert_eq!(cues.join(" "), text);
+    }
+
+    #[test]
+    fn long_cues_are_split_by_characters_and_time() {
+        let long = Segment {
+            start: 0.0,
+            end: 20.0,
+            text: vec!["subtitle"; 30].join(" "),
+            speaker: Some(1),
+            channel: None,
+        };
+        let cues = limit_cues(&[long], CueLimits::default());
+        assert!(cues.len() >= 4, "got {} cues", cues.len());
+        for cue in &cues {
+            assert!(cue.end - cue.start <= 6.0 + 1e-9);
+            assert!(cue.text.lines().count() <= 2);
+            assert!(cue.text.lines().all(|l| l.chars().count() <= 42));
+            assert_eq!(cue.speaker, Some(1));
+        }
+        assert_eq!(cues.first().unwrap().start, 0.0);
+        assert!((cues.last().unwrap().end - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn short_cues_are_kept() {
+        let cue = Segment { start: 1.0, end: 2.0, text: "Hello there".into(), speaker: None, channel: None };
+        let cues = limit_cues(std::slice::from_ref(&cue), CueLimits::default());
+        assert_eq!(cues.len(), 1);
+        assert_eq!((cues[0].start, cues[0].end, cues[0].text.as_str()), (1.0, 2.0, "Hello there"));
+    }
+
+    #[test]
+    fn line_breaks_end_cues() {
+        assert_eq!(cue_text("first part\n\nsecond part"), vec!["first part", "second part"]);
+    }
+
+    #[test]
+    fn overlapping_speakers_from_the_store_make_a_valid_timeline() {
+        let seg = |start: f64, end: f64, text: &str, speaker: u32| Segment {
+            start,
+            end,
+            text: text.into(),
+            speaker: Some(speaker),
+            channel: None,
+        };
+        // Finalization order, with speaker 1's turn overlapping speaker 0's
+        let store = vec![
+            seg(4.0, 6.0, "Later.", 0),
+            seg(0.5, 3.0, "Hello there.", 0),
+            seg(2.5, 3.5, "Hi!", 1),
+        ];
+        assert!(timeline(&store, 6.0).is_err());
+
+        let timeline = session_timeline(store, None).unwrap();
+        assert_eq!(timeline.duration, 6.0);
+        let spoken: Vec<_> = timeline.cues.iter().filter(|c| !c.text.is_empty()).collect();
+        assert_eq!(spoken.len(), 2);
+        assert_eq!((spoken[0].start, spoken[0].end), (0.5, 3.5));
+        assert_eq!(spoken[0].text, "Speaker 0: Hello there. Speaker 1: Hi!");
+        assert_eq!(spoken[0].speaker, None);
+        assert_eq!((spoken[1].start, spoken[1].end, spoken[1].speaker), (4.0, 6.0, Some(0)));
+    }
+}
@@ -1,58 +1,144 @@
-use std::{fs, path::PathBuf};
-use serde_json::Value;
+use serde::Serialize;
+use std::{fs, path::Path, path::PathBuf};
 use tauri::{AppHandle, Emitter};
 
-pub async fn transcribe_file(path: PathBuf, app: AppHandle) {
-    println!("🚀 Starting file transcription");
+use crate::backend::{self, TranscriptionBackend};
+use crate::model::{FileTranscript, TranscriptEvent};
+use crate::options::FileOptions;
+use crate::wav::{self, Pcm16Chunks};
 
-    let api_key = std::env::var("DEEPGRAM_API_KEY")
-        .expect("DEEPGRAM_API_KEY not set");
+/// Audio formats offered by the file picker
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "flac", "ogg"];
 
-    let audio_bytes = match fs::read(&path) {
-        Ok(b) => b,
-        Err(e) => {
-            eprintln!("❌ Failed to read file: {}", e);
-            return;
-        }
-    };
+/// PCM WAV files longer than this are uploaded in pieces of this length, with a
+/// `file_transcribe_progress` event per piece
+const CHUNK_SECS: u64 = 600;
 
-    let client = reqwest::Client::new();
+/// Payload of `file_transcribe_progress`
+#[derive(Debug, Clone, Serialize)]
+struct FileProgress<'a> {
+    file_name: &'a str,
+    /// Pieces transcribed so far, out of `total`
+    done: usize,
+    total: usize,
+}
 
-    let response = client
-        .post("https://api.deepgram.com/v1/listen?punctuate=true")
-        .header("Authorization", format!("Token {}", api_key))
-        .header("Content-Type", "audio/*")
-        .body(audio_bytes)
-        .send()
-        .await;
+/// 📂 Transcribe a recording from disk with a backend's file API (`None` → the default).
+///
+/// Emits `file_transcribe_started`, `file_transcribe_progress` (long WAV files),
+/// `transcript` per piece, then `file_transcribe_done` or `file_transcribe_error`.
+/// With a summary or detected language, `summary` / `detected_language` are
+/// emitted before `file_transcribe_done`.
+/// The result replaces the session transcript store, so exports pick it up.
+pub async fn transcribe_file(
+    path: PathBuf,
+    app: AppHandle,
+    backend: Option<String>,
+    options: FileOptions,
+) -> Result<FileTranscript, String> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned());
+    println!("🚀 Starting file transcription: {:?}", path);
+    crate::transcript::clear();
+    let _ = app.emit("file_transcribe_started", serde_json::json!({ "file_name": file_name }));
 
-    let response = match response {
-        Ok(r) => r,
+    match run(&path, &file_name, &app, backend.as_deref(), &options).await {
+        Ok(result) => {
+            println!("✅ Transcribed {} ({} utterances)", file_name, result.segments.len());
+            crate::transcript::load_file(&result);
+            match &result.detected_language {
+                Some(detected) => {
+                    println!("🌐 Detected language: {} ({:?})", detected.language, detected.confidence);
+                    let _ = app.emit("detected_language", detected);
+                }
+                None if options.detect_language => println!("⚠️ Language detection requested but not reported"),
+                None => {}
+            }
+            if let Some(summary) = &result.summary {
+                let _ = app.emit("summary", serde_json::json!({ "file_name": file_name, "text": summary }));
+            }
+            let _ = app.emit("file_transcribe_done", &result);
+            Ok(result)
+        }
         Err(e) => {
-            eprintln!("❌ HTTP error: {}", e);
-            return;
+            eprintln!("❌ File transcription failed: {}", e);
+            let _ = app.emit("file_transcribe_error", serde_json::json!({ "file_name": file_name, "error": e }));
+            Err(e)
         }
-    };
+    }
+}
 
-    let json: Value = match response.json().await {
-        Ok(j) => j,
-        Err(e) => {
-            eprintln!("❌ JSON parse error: {}", e);
-            return;
+async fn run(
+    path: &Path,
+    file_name: &str,
+    app: &AppHandle,
+    backend: Option<&str>,
+    options: &FileOptions,
+) -> Result<FileTranscript, String> {
+    let backend = backend::select(backend)?;
+    let legacy_event = crate::config::load().legacy_transcript_event;
+
+    // Long PCM WAV recordings can be split without decoding; everything else goes up whole
+    if let Ok(mut chunks) = Pcm16Chunks::open(path) {
+        let chunk_frames = CHUNK_SECS * chunks.sample_rate as u64;
+        if chunks.frames > chunk_frames {
+            return transcribe_chunked(backend.as_ref(), &mut chunks, chunk_frames as usize, file_name, app, options, legacy_event).await;
         }
-    };
+    }
+
+    let mime = crate::media::audio_mime(path)?;
+    let audio_bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    println!("🏷️ Uploading {} as {} ({})", file_name, mime.unwrap_or("auto-detected audio"), backend.name());
+    let mut result = backend.transcribe_file(audio_bytes, mime, options).await?;
+    result.file_name = file_name.to_string();
+    show_piece(app, &result, legacy_event);
+    Ok(result)
+}
 
-    println!("📨 Deepgram JSON: {}", json);
+/// Upload a WAV file `chunk_frames` at a time, merging the pieces onto one timeline
+async fn transcribe_chunked(
+    backend: &dyn TranscriptionBackend,
+    chunks: &mut Pcm16Chunks,
+    chunk_frames: usize,
+    file_name: &str,
+    app: &AppHandle,
+    options: &FileOptions,
+    legacy_event: bool,
+) -> Result<FileTranscript, String> {
+    let total = chunks.frames.div_ceil(chunk_frames as u64) as usize;
+    println!("✂️ Splitting {} into {} pieces of {}s", file_name, total, CHUNK_SECS);
+    let mut merged = FileTranscript { file_name: file_name.to_string(), ..Default::default() };
 
-    let transcript = json["results"]["channels"][0]["alternatives"][0]["transcript"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
+    for index in 0..total {
+        let samples = chunks.next_chunk(chunk_frames)?;
+        if samples.is_empty() {
+            break;
+        }
+        let bytes = wav::encode_pcm16(&samples, chunks.sample_rate, chunks.channels)?;
+        let piece = backend.transcribe_file(bytes, Some("audio/wav"), options).await?;
+        show_piece(app, &piece, legacy_event);
+        let offset = (index * chunk_frames) as f64 / chunks.sample_rate as f64;
+        merged.append(piece, offset);
+        let _ = app.emit("file_transcribe_progress", FileProgress { file_name, done: index + 1, total });
+    }
+    Ok(merged)
+}
 
-    if transcript.is_empty() {
+/// Show a transcribed piece's text as it arrives
+fn show_piece(app: &AppHandle, piece: &FileTranscript, legacy_event: bool) {
+    if piece.text.trim().is_empty() {
         println!("⚠️ Empty transcript");
-    } else {
-        println!("📝 TRANSCRIPT: {}", transcript);
-        let _ = app.emit("transcript", transcript);
+        return;
     }
+    crate::transcript::emit(app, TranscriptEvent {
+        text: piece.text.clone(),
+        is_final: true,
+        confidence: None,
+        start: None,
+        end: None,
+        speaker: None,
+        t_start: None,
+    }, legacy_event);
 }
@@ -0,0 +1,771 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current on-disk history format version
+pub const HISTORY_FORMAT_VERSION: u32 = 1;
+
+/// 🗃️ Saved history file with session metadata
+///
+/// Older files are a bare JSON array of strings; [`HistoryFile::parse`] accepts both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryFile {
+    pub version: u32,
+    /// Unix seconds
+    pub created_at: u64,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Set for files written by [`save_session`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Unix seconds; written when a session ends normally. A session file without
+    /// it was left behind by a crash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+    pub entries: Vec<String>,
+}
+
+impl HistoryFile {
+    /// Parse either the metadata format or a legacy bare array (which gets `fallback_created_at`)
+    pub fn parse(content: &str, fallback_created_at: u64) -> Result<HistoryFile, String> {
+        if let Ok(file) = serde_json::from_str::<HistoryFile>(content) {
+            return Ok(file);
+        }
+        let entries: Vec<String> = serde_json::from_str(content).map_err(|e| format!("Invalid history file: {}", e))?;
+        Ok(HistoryFile {
+            version: 0,
+            created_at: fallback_created_at,
+            language: None,
+            model: None,
+            session_id: None,
+            device: None,
+            ended_at: None,
+            entries,
+        })
+    }
+}
+
+/// 📋 Lightweight listing entry (no transcript text)
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySummary {
+    pub path: String,
+    pub created_at: u64,
+    pub language: Option<String>,
+    pub model: Option<String>,
+    pub entries: usize,
+}
+
+/// 🔍 Optional criteria for `list_history`; unset fields match everything
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// Language code; "en" also matches regional variants like "en-US"
+    pub language: Option<String>,
+    pub model: Option<String>,
+    /// Inclusive unix-seconds range
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, summary: &HistorySummary) -> bool {
+        if let Some(want) = &self.language {
+            let Some(have) = &summary.language else { return false };
+            let primary = have.split('-').next().unwrap_or(have);
+            if !(have.eq_ignore_ascii_case(want) || primary.eq_ignore_ascii_case(want)) {
+                return false;
+            }
+        }
+        if let Some(want) = &self.model {
+            if !summary.model.as_deref().is_some_and(|m| m.eq_ignore_ascii_case(want)) {
+                return false;
+            }
+        }
+        if self.from.is_some_and(|from| summary.created_at < from) {
+            return false;
+        }
+        if self.to.is_some_and(|to| summary.created_at > to) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Tauri's per-app data directory, recorded at startup by [`set_app_data_dir`]
+static APP_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Record the platform app data directory (`app.path().app_data_dir()`)
+pub fn set_app_data_dir(dir: PathBuf) {
+    let _ = APP_DATA_DIR.set(dir);
+}
+
+/// 📁 Directory history files are saved to, and the one place every history command
+/// resolves it: the configured `history_dir`, else the app data directory, else
+/// `$XDG_DATA_HOME/heard_it`, else `$HOME/.local/share/heard_it`
+pub fn history_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = crate::config::load().history_dir.filter(|d| !d.trim().is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = APP_DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(x) if !x.is_empty() => return Ok(PathBuf::from(x).join("heard_it")),
+        _ => {}
+    }
+    match std::env::var("HOME") {
+        Ok(h) if !h.is_empty() => Ok(PathBuf::from(h).join(".local/share/heard_it")),
+        _ => Err("Could not resolve a data directory (no app data dir, XDG_DATA_HOME or HOME)".into()),
+    }
+}
+
+/// 📁 Use `dir` for history from now on (an empty string restores the default).
+/// The directory is created if missing and must be writable; returns the resolved path.
+pub fn set_history_dir(dir: &str) -> Result<PathBuf, String> {
+    let dir = dir.trim();
+    if dir.is_empty() {
+        crate::config::update(|s| s.history_dir = None)?;
+        return history_dir();
+    }
+    let path = PathBuf::from(dir);
+    if !path.is_absolute() {
+        return Err(format!("History directory must be an absolute path: {:?}", dir));
+    }
+    fs::create_dir_all(&path).map_err(|e| format!("Cannot create history directory {:?}: {}", path, e))?;
+    let probe = path.join(".heard_it_write_test");
+    fs::write(&probe, b"").map_err(|e| format!("History directory {:?} is not writable: {}", path, e))?;
+    let _ = fs::remove_file(&probe);
+
+    let stored = path.to_string_lossy().to_string();
+    crate::config::update(|s| s.history_dir = Some(stored))?;
+    println!("📁 History directory set to {:?}", path);
+    Ok(path)
+}
+
+/// Unix timestamp (seconds) embedded in `transcript_history_<secs>.json` or `session_<millis>.json`
+fn filename_timestamp(path: &Path) -> Option<u64> {
+    let stem = path.file_stem()?.to_str()?;
+    if let Some(secs) = stem.strip_prefix("transcript_history_") {
+        return secs.parse().ok();
+    }
+    stem.strip_prefix("session_")?.parse::<u64>().ok().map(|millis| millis / 1000)
+}
+
+/// 📂 Paths of all history files (`transcript_history_<secs>.json` and `session_<id>.json`), newest (by embedded timestamp) first.
+/// A missing directory is an empty list.
+pub fn list_paths() -> Result<Vec<String>, String> {
+    let dir = history_dir()?;
+    let read = match fs::read_dir(&dir) {
+        Ok(r) => r,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut found: Vec<(u64, PathBuf)> = read
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|p| Some((filename_timestamp(&p)?, p)))
+        .collect();
+    found.sort_by_key(|(ts, _)| std::cmp::Reverse(*ts));
+    Ok(found.into_iter().map(|(_, p)| p.to_string_lossy().to_string()).collect())
+}
+
+/// 📖 Read the transcript entries of a saved history file (either format)
+pub fn load(path: &Path) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    Ok(HistoryFile::parse(&content, filename_timestamp(path).unwrap_or(0))?.entries)
+}
+
+/// 🔒 Resolve a bare history filename inside the history dir, rejecting anything else
+pub fn resolve(filename: &str) -> Result<PathBuf, String> {
+    let path = Path::new(filename);
+    let is_plain_name = path.components().count() == 1 && path.file_name().is_some_and(|n| n == filename);
+    if !is_plain_name || filename_timestamp(path).is_none() || path.extension().is_none_or(|e| e != "json") {
+        return Err(format!("Not a history file name: {:?}", filename));
+    }
+    Ok(history_dir()?.join(filename))
+}
+
+/// Whether `path` is the file the running history session saves to
+fn is_current_session(path: &Path) -> bool {
+    let session = SESSION.lock().unwrap();
+    let name = path.file_name().and_then(|n| n.to_str());
+    session.as_ref().is_some_and(|s| name == Some(format!("session_{}.json", s.id).as_str()))
+}
+
+/// 🗑️ Delete one history file. The path is canonicalized and must name a history
+/// file directly inside [`history_dir`]; anything else is refused.
+pub fn delete(path: &str) -> Result<(), String> {
+    let dir = history_dir()?
+        .canonicalize()
+        .map_err(|e| format!("History directory unavailable: {}", e))?;
+    let path = Path::new(path)
+        .canonicalize()
+        .map_err(|e| format!("Cannot delete {:?}: {}", path, e))?;
+    let is_history_file = filename_timestamp(&path).is_some() && path.extension().is_some_and(|e| e == "json");
+    if path.parent() != Some(dir.as_path()) || !is_history_file || !path.is_file() {
+        return Err(format!("Refusing to delete {:?}: not a history file in {:?}", path, dir));
+    }
+    if is_current_session(&path) {
+        return Err("Cannot delete the history of the session being recorded".into());
+    }
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete {:?}: {}", path, e))?;
+    println!("🗑️ Deleted history {:?}", path);
+    Ok(())
+}
+
+/// 🗑️ Delete every history file (the running session's excepted); returns how many
+/// were removed. Other files in the directory are left alone.
+pub fn delete_all() -> Result<usize, String> {
+    let mut deleted = 0;
+    for path in list_paths()? {
+        let path = PathBuf::from(path);
+        if path.extension().is_none_or(|e| e != "json") || is_current_session(&path) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => deleted += 1,
+            Err(e) => eprintln!("⚠️ Failed to delete {:?}: {}", path, e),
+        }
+    }
+    println!("🗑️ Deleted {} history files", deleted);
+    Ok(deleted)
+}
+
+/// 👀 The first `max_chars` characters of a saved history (entries joined by spaces).
+///
+/// The file is parsed as it is read and reading stops once the preview is full, so
+/// long sessions aren't loaded just to show their first line.
+pub fn preview(filename: &str, max_chars: usize) -> Result<String, String> {
+    let path = resolve(filename)?;
+    let file = fs::File::open(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    preview_from_reader(std::io::BufReader::new(file), max_chars)
+}
+
+fn preview_from_reader(reader: impl std::io::Read, max_chars: usize) -> Result<String, String> {
+    let mut preview = Preview { text: String::new(), chars: 0, max_chars };
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    let parsed = serde::de::DeserializeSeed::deserialize(PreviewVisitor(&mut preview), &mut de);
+    // Stopping early leaves the document unfinished, which the deserializer reports
+    if let Err(e) = parsed {
+        if !preview.is_full() {
+            return Err(format!("Invalid history file: {}", e));
+        }
+    }
+    Ok(truncate_chars(&preview.text, max_chars))
+}
+
+/// Entries collected for [`preview`]
+struct Preview {
+    text: String,
+    chars: usize,
+    max_chars: usize,
+}
+
+impl Preview {
+    fn push(&mut self, entry: &str) {
+        if !self.text.is_empty() {
+            self.text.push(' ');
+            self.chars += 1;
+        }
+        let entry = entry.trim();
+        self.text.push_str(entry);
+        self.chars += entry.chars().count();
+    }
+
+    fn is_full(&self) -> bool {
+        self.chars > self.max_chars
+    }
+}
+
+/// Reads a history file (metadata object or legacy array) into a [`Preview`],
+/// skipping everything but the entries
+struct PreviewVisitor<'a>(&'a mut Preview);
+
+impl<'de> serde::de::DeserializeSeed<'de> for PreviewVisitor<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for PreviewVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a history file")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(entry) = seq.next_element::<String>()? {
+            self.0.push(&entry);
+            if self.0.is_full() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut has_entries = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key != "entries" {
+                map.next_value::<serde::de::IgnoredAny>()?;
+                continue;
+            }
+            map.next_value_seed(EntriesSeed(&mut *self.0))?;
+            if self.0.is_full() {
+                return Ok(());
+            }
+            has_entries = true;
+        }
+        match has_entries {
+            true => Ok(()),
+            false => Err(serde::de::Error::missing_field("entries")),
+        }
+    }
+}
+
+/// The `entries` array of a metadata history file
+struct EntriesSeed<'a>(&'a mut Preview);
+
+impl<'de> serde::de::DeserializeSeed<'de> for EntriesSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(PreviewVisitor(self.0))
+    }
+}
+
+/// Cut `text` to `max_chars` characters, marking the cut with an ellipsis
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((byte, _)) => format!("{}…", text[..byte].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// 🧷 The history session being auto-saved (see [`begin_session`])
+struct HistorySession {
+    id: String,
+    created_at: u64,
+    device: Option<String>,
+    model: Option<String>,
+    language: Option<String>,
+}
+
+static SESSION: Mutex<Option<HistorySession>> = Mutex::new(None);
+
+/// 🧷 Start a history session; [`save_session`] then keeps rewriting one
+/// `session_<id>.json` instead of adding a file per save. The id is the start
+/// time in unix milliseconds.
+pub fn begin_session(device: Option<String>, model: Option<String>, language: Option<String>) -> Result<String, String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?;
+    let mut session = SESSION.lock().unwrap();
+    // Two sessions within the same millisecond must not share a file
+    let mut millis = now.as_millis() as u64;
+    if let Some(prev) = session.as_ref().and_then(|s| s.id.parse::<u64>().ok()) {
+        millis = millis.max(prev + 1);
+    }
+    let id = millis.to_string();
+    *session = Some(HistorySession { id: id.clone(), created_at: millis / 1000, device, model, language });
+    println!("🧷 History session {} started", id);
+    Ok(id)
+}
+
+/// 💾 Overwrite the current session's file with `entries`; returns its path.
+///
+/// Written to a temporary file and renamed, so a crash mid-save leaves the previous
+/// snapshot intact.
+pub fn save_session(entries: Vec<String>) -> Result<PathBuf, String> {
+    let session = SESSION.lock().unwrap();
+    let session = session.as_ref().ok_or("No history session started (call begin_session)")?;
+    let file = HistoryFile {
+        version: HISTORY_FORMAT_VERSION,
+        created_at: session.created_at,
+        language: session.language.clone(),
+        model: session.model.clone(),
+        session_id: Some(session.id.clone()),
+        device: session.device.clone(),
+        ended_at: None,
+        entries,
+    };
+    let dir = history_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app dir: {}", e))?;
+    let path = dir.join(format!("session_{}.json", session.id));
+    write_atomically(&path, &file)?;
+    Ok(path)
+}
+
+/// Write through a temporary file and a rename, so readers never see half a file
+fn write_atomically(path: &Path, file: &HistoryFile) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))
+}
+
+/// Path of `session_<id>.json`, rejecting ids that aren't plain numbers
+fn session_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid session id: {:?}", id));
+    }
+    Ok(history_dir()?.join(format!("session_{}.json", id)))
+}
+
+/// Stamp a session file with `ended_at`; a session that never saved has no file to mark
+fn mark_ended(path: &Path) -> Result<(), String> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to read {:?}: {}", path, e)),
+    };
+    let mut file = HistoryFile::parse(&content, 0)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_secs();
+    file.ended_at.get_or_insert(now);
+    write_atomically(path, &file)
+}
+
+/// 🏁 Close the current history session (normal stop): its file gets the
+/// `ended_at` marker, so it isn't offered for recovery
+pub fn end_session() -> Result<(), String> {
+    let Some(session) = SESSION.lock().unwrap().take() else { return Ok(()) };
+    mark_ended(&session_path(&session.id)?)?;
+    println!("🏁 History session {} ended", session.id);
+    Ok(())
+}
+
+/// 🩹 A session file left without an `ended_at` marker (the app died mid-recording)
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoverableSession {
+    pub session_id: String,
+    pub path: String,
+    pub created_at: u64,
+    pub device: Option<String>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub entries: Vec<String>,
+}
+
+/// 🩹 The newest unfinished session with some text, if any (the running session excluded)
+pub fn recoverable_session() -> Result<Option<RecoverableSession>, String> {
+    let dir = history_dir()?;
+    let read = match fs::read_dir(&dir) {
+        Ok(r) => r,
+        Err(_) => return Ok(None),
+    };
+    let current = SESSION.lock().unwrap().as_ref().map(|s| s.id.clone());
+
+    let mut found: Option<RecoverableSession> = None;
+    for entry in read.flatten() {
+        let path = entry.path();
+        let is_session = path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.starts_with("session_"));
+        if !is_session || path.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let Some(fallback_ts) = filename_timestamp(&path) else { continue };
+        // The crash may have left anything behind: skip what doesn't parse
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| HistoryFile::parse(&content, fallback_ts));
+        let file = match parsed {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("⚠️ Skipping {:?}: {}", path, e);
+                continue;
+            }
+        };
+        let Some(id) = file.session_id else { continue };
+        let is_empty = file.entries.iter().all(|e| e.trim().is_empty());
+        if file.ended_at.is_some() || current.as_deref() == Some(id.as_str()) || is_empty {
+            continue;
+        }
+        if found.as_ref().is_some_and(|f| f.created_at >= file.created_at) {
+            continue;
+        }
+        found = Some(RecoverableSession {
+            session_id: id,
+            path: path.to_string_lossy().to_string(),
+            created_at: file.created_at,
+            device: file.device,
+            model: file.model,
+            language: file.language,
+            entries: file.entries,
+        });
+    }
+    Ok(found)
+}
+
+/// 📖 The entries saved for session `session_id`
+pub fn session_entries(session_id: &str) -> Result<Vec<String>, String> {
+    load(&session_path(session_id)?)
+}
+
+/// 🩹 Mark an unfinished session as handled (restored or declined), so it isn't offered again
+pub fn close_recovered(session_id: &str) -> Result<(), String> {
+    mark_ended(&session_path(session_id)?)
+}
+
+/// List saved histories matching `filter`, newest first. A missing directory is an empty list.
+pub fn list(filter: &HistoryFilter) -> Result<Vec<HistorySummary>, String> {
+    let dir = history_dir()?;
+    let read = match fs::read_dir(&dir) {
+        Ok(r) => r,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut out = Vec::new();
+    for entry in read.flatten() {
+        let path = entry.path();
+        let Some(fallback_ts) = filename_timestamp(&path) else { continue };
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        let file = match HistoryFile::parse(&content, fallback_ts) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("⚠️ Skipping {:?}: {}", path, e);
+                continue;
+            }
+        };
+        let summary = HistorySummary {
+            path: path.to_string_lossy().to_string(),
+            created_at: file.created_at,
+            language: file.language,
+            model: file.model,
+            entries: file.entries.len(),
+        };
+        if filter.matches(&summary) {
+            out.push(summary);
+        }
+    }
+
+    out.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(out)
+}
+
+/// Hits returned by `search_history` when the caller sets no cap
+pub const DEFAULT_MAX_HITS: usize = 100;
+/// Characters of context kept on each side of a match
+const SNIPPET_CONTEXT: usize = 40;
+
+/// 🔎 A match in a saved history
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub path: String,
+    pub created_at: u64,
+    /// Index of the transcript entry within the file
+    pub entry: usize,
+    /// 1-based line within the entry
+    pub line: usize,
+    /// The match with up to [`SNIPPET_CONTEXT`] characters either side
+    pub snippet: String,
+}
+
+/// Build the matcher: `query` is a regex when `regex` is set, otherwise literal text;
+/// matching is case-insensitive either way
+pub fn search_pattern(query: &str, regex: bool) -> Result<regex::Regex, String> {
+    if query.trim().is_empty() {
+        return Err("Search query is empty".into());
+    }
+    let pattern = if regex { query.to_string() } else { regex::escape(query) };
+    regex::RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// `line` around the byte range `start..end`, cut to whole characters
+fn snippet(line: &str, start: usize, end: usize) -> String {
+    let before = &line[..start];
+    let cut = before.char_indices().rev().nth(SNIPPET_CONTEXT - 1).map(|(i, _)| i).unwrap_or(0);
+    let after = &line[end..];
+    let keep = after.char_indices().nth(SNIPPET_CONTEXT).map(|(i, _)| i).unwrap_or(after.len());
+
+    let mut out = String::new();
+    if cut > 0 {
+        out.push('…');
+    }
+    out.push_str(&line[cut..end + keep]);
+    if keep < after.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// Matches of `pattern` in one history file, at most one per line
+fn search_file(file: &HistoryFile, path: &str, pattern: &regex::Regex, max_hits: usize, hits: &mut Vec<SearchHit>) {
+    for (entry, text) in file.entries.iter().enumerate() {
+        for (line_index, line) in text.lines().enumerate() {
+            if hits.len() >= max_hits {
+                return;
+            }
+            if let Some(m) = pattern.find(line) {
+                hits.push(SearchHit {
+                    path: path.to_string(),
+                    created_at: file.created_at,
+                    entry,
+                    line: line_index + 1,
+                    snippet: snippet(line, m.start(), m.end()),
+                });
+            }
+        }
+    }
+}
+
+/// 🔎 Search every saved history (newest first) for `pattern`, stopping at `max_hits`
+pub fn search(pattern: &regex::Regex, max_hits: usize) -> Result<Vec<SearchHit>, String> {
+    let mut hits = Vec::new();
+    for path in list_paths()? {
+        if hits.len() >= max_hits {
+            break;
+        }
+        let fallback_ts = filename_timestamp(Path::new(&path)).unwrap_or(0);
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        match HistoryFile::parse(&content, fallback_ts) {
+            Ok(file) => search_file(&file, &path, pattern, max_hits, &mut hits),
+            Err(e) => eprintln!("⚠️ Skipping {:?}: {}", path, e),
+        }
+    }
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(language: Option<&str>, model: Option<&str>, created_at: u64) -> HistorySummary {
+        HistorySummary {
+            path: String::new(),
+            created_at,
+            language: language.map(String::from),
+            model: model.map(String::from),
+            entries: 1,
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let f = HistoryFilter::default();
+        assert!(f.matches(&summary(None, None, 0)));
+        assert!(f.matches(&summary(Some("de"), Some("nova-2"), 42)));
+    }
+
+    #[test]
+    fn language_matches_primary_subtag_case_insensitively() {
+        let f = HistoryFilter { language: Some("en".into()), ..Default::default() };
+        assert!(f.matches(&summary(Some("en"), None, 0)));
+        assert!(f.matches(&summary(Some("EN-us"), None, 0)));
+        assert!(!f.matches(&summary(Some("es"), None, 0)));
+        assert!(!f.matches(&summary(None, None, 0)));
+    }
+
+    #[test]
+    fn model_and_date_range_combine() {
+        let f = HistoryFilter {
+            model: Some("nova-2".into()),
+            from: Some(100),
+            to: Some(200),
+            ..Default::default()
+        };
+        assert!(f.matches(&summary(None, Some("nova-2"), 100)));
+        assert!(f.matches(&summary(None, Some("Nova-2"), 200)));
+        assert!(!f.matches(&summary(None, Some("nova-2"), 99)));
+        assert!(!f.matches(&summary(None, Some("nova-2"), 201)));
+        assert!(!f.matches(&summary(None, Some("base"), 150)));
+    }
+
+    #[test]
+    fn parses_metadata_and_legacy_formats() {
+        let legacy = HistoryFile::parse(r#"["a", "b"]"#, 7).unwrap();
+        assert_eq!(legacy.created_at, 7);
+        assert_eq!(legacy.entries, vec!["a", "b"]);
+        assert!(legacy.language.is_none());
+
+        let meta = HistoryFile::parse(
+            r#"{"version":1,"created_at":9,"language":"es","model":"nova-2","entries":["hola"]}"#,
+            7,
+        )
+        .unwrap();
+        assert_eq!(meta.created_at, 9);
+        assert_eq!(meta.language.as_deref(), Some("es"));
+        assert_eq!(meta.entries, vec!["hola"]);
+
+        assert!(HistoryFile::parse("{}", 0).is_err());
+    }
+
+    #[test]
+    fn preview_reads_both_formats() {
+        let legacy = preview_from_reader(r#"["  hello ", "world"]"#.as_bytes(), 20).unwrap();
+        assert_eq!(legacy, "hello world");
+
+        let meta = r#"{"version":1,"created_at":9,"entries":["hola","mundo"],"ended_at":12}"#;
+        assert_eq!(preview_from_reader(meta.as_bytes(), 20).unwrap(), "hola mundo");
+        assert_eq!(preview_from_reader(meta.as_bytes(), 6).unwrap(), "hola m…");
+
+        assert!(preview_from_reader(r#"{"version":1}"#.as_bytes(), 20).is_err());
+        assert!(preview_from_reader(r#"["unfinished""#.as_bytes(), 20).is_err());
+    }
+
+    #[test]
+    fn preview_stops_reading_once_full() {
+        // Everything after the first entry is never parsed
+        let content = r#"{"entries":["one two three", "four", not json"#;
+        assert_eq!(preview_from_reader(content.as_bytes(), 7).unwrap(), "one two…");
+    }
+
+    #[test]
+    fn session_files_are_history_files() {
+        assert_eq!(filename_timestamp(Path::new("session_1700000000123.json")), Some(1_700_000_000));
+        assert_eq!(filename_timestamp(Path::new("transcript_history_42.json")), Some(42));
+        assert_eq!(filename_timestamp(Path::new("session_abc.json")), None);
+        assert!(session_path("../x").is_err());
+    }
+
+    #[test]
+    fn search_is_case_insensitive_and_literal_by_default() {
+        let file = HistoryFile::parse(r#"["first line\nThe Budget (draft) is due", "nothing here"]"#, 5).unwrap();
+        let mut hits = Vec::new();
+        search_file(&file, "p", &search_pattern("budget (DRAFT)", false).unwrap(), 10, &mut hits);
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].entry, hits[0].line, hits[0].created_at), (0, 2, 5));
+        assert_eq!(hits[0].snippet, "The Budget (draft) is due");
+
+        let mut hits = Vec::new();
+        search_file(&file, "p", &search_pattern(r"^(first|nothing)", true).unwrap(), 1, &mut hits);
+        assert_eq!(hits.len(), 1, "capped at max_hits");
+        assert!(search_pattern("(", true).is_err());
+        assert!(search_pattern("  ", false).is_err());
+    }
+
+    #[test]
+    fn snippets_keep_context_on_char_boundaries() {
+        let line = format!("{}needle{}", "é".repeat(60), "ü".repeat(60));
+        let start = line.find("needle").unwrap();
+        let s = snippet(&line, start, start + 6);
+        assert_eq!(s, format!("…{}needle{}…", "é".repeat(40), "ü".repeat(40)));
+    }
+
+    #[test]
+    fn delete_refuses_files_outside_the_history_dir() {
+        let stray = std::env::temp_dir().join(format!("transcript_history_{}.json", std::process::id()));
+        fs::write(&stray, "[]").unwrap();
+        assert!(delete(stray.to_str().unwrap()).is_err());
+        assert!(stray.exists());
+        fs::remove_file(&stray).unwrap();
+    }
+
+    #[test]
+    fn ended_marker_round_trips() {
+        let open = HistoryFile::parse(r#"{"version":1,"created_at":9,"session_id":"9000","entries":["hi"]}"#, 0).unwrap();
+        assert!(open.ended_at.is_none());
+        let mut ended = open.clone();
+        ended.ended_at = Some(10);
+        let json = serde_json::to_string(&ended).unwrap();
+        assert_eq!(HistoryFile::parse(&json, 0).unwrap().ended_at, Some(10));
+        assert!(!serde_json::to_string(&open).unwrap().contains("ended_at"));
+    }
+}
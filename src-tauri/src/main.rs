@@ -1,35 +1,206 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+// Keep stdout clean for JSON lines in `HEARD_IT_STDOUT_JSON` mode: every `println!`
+// in the modules below goes to stderr instead while it's on.
+macro_rules! println {
+    ($($arg:tt)*) => {
+        if $crate::stdout_json::enabled() {
+            eprintln!($($arg)*)
+        } else {
+            std::println!($($arg)*)
+        }
+    };
+}
+
+mod analysis;
 mod audio;
+mod backend;
+mod config;
 mod deepgram;
+mod export;
+mod file_transcribe;
+mod history;
+mod media;
+mod model;
+mod openai;
+mod options;
+mod recorder;
+mod session;
+mod stdout_json;
+mod transcript;
+mod vad;
+#[cfg(feature = "whisper")]
+mod whisper;
+mod wav;
+mod wav_stream;
+mod worker;
 
-use tauri::AppHandle;
-use tokio::sync::mpsc;
-use tokio::sync::mpsc::UnboundedSender;
-use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_dialog::DialogExt;
 use std::fs;
 use serde_json;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::io::Read;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::thread;
 
-static AUDIO_TX: Mutex<Option<UnboundedSender<Vec<i16>>>> = Mutex::new(None);
+static AUDIO_TX: Mutex<Option<audio::queue::FrameSender>> = Mutex::new(None);
+/// Device the current recording was started on ("" = system default)
+static ACTIVE_DEVICE: Mutex<Option<String>> = Mutex::new(None);
+/// Stop flag of the current session's worker forwarding thread
+static FORWARD_STOP: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
 
 /// 🎙️ List available mic devices (CPAL)
 #[tauri::command]
-fn list_mic_devices() -> Vec<String> {
-    audio::list_input_devices()
+fn list_mic_devices(app: AppHandle) -> Vec<String> {
+    let devices = audio::list_input_devices();
+    check_preferred_device(&app, &devices);
+    devices
+}
+
+/// 🔁 Offer to switch back when the remembered mic reappears while recording on another device
+fn check_preferred_device(app: &AppHandle, devices: &[String]) {
+    let Some(preferred) = config::load().preferred_device else { return };
+    let active = ACTIVE_DEVICE.lock().unwrap().clone();
+    if let Some(active) = active {
+        if active != preferred && devices.contains(&preferred) {
+            let _ = app.emit("preferred_device_available", preferred);
+        }
+    }
+}
+
+/// 🔌 Watch for input devices being plugged in or removed (`devices_changed` events)
+#[tauri::command]
+fn start_device_watcher(app: AppHandle) {
+    let handle = app.clone();
+    audio::hotplug::start_watcher(app, move |devices| {
+        check_preferred_device(&handle, devices);
+        audio::notify_devices_changed(devices.to_vec());
+    });
+}
+
+/// 🔌 Stop the device watcher
+#[tauri::command]
+fn stop_device_watcher() {
+    audio::hotplug::stop_watcher();
+}
+
+/// 🎙️ Remembered input device used when `start_recording` gets an empty device name
+#[tauri::command]
+fn get_preferred_device() -> Option<String> {
+    config::load().preferred_device
+}
+
+/// ⚙️ Read persisted settings (API keys are left out; see `get_api_key_status`)
+#[tauri::command]
+fn get_settings() -> config::Settings {
+    config::Settings { deepgram_api_key: None, openai_api_key: None, ..config::load() }
+}
+
+/// ⚙️ Persist settings, keeping the stored API keys
+#[tauri::command]
+fn save_settings(settings: config::Settings) -> Result<(), String> {
+    let stored = config::load();
+    config::save(&config::Settings {
+        deepgram_api_key: stored.deepgram_api_key,
+        openai_api_key: stored.openai_api_key,
+        ..settings
+    })
+}
+
+/// 🔑 Store the Deepgram API key in config.json (an empty key removes it)
+#[tauri::command]
+fn set_api_key(key: String) -> Result<deepgram::ApiKeyStatus, String> {
+    let key = key.trim().to_string();
+    let stored = (!key.is_empty()).then_some(config::ApiKey(key));
+    config::update(|s| s.deepgram_api_key = stored)?;
+    let status = deepgram::api_key_status();
+    println!("🔑 API key updated (source: {:?})", status.source);
+    Ok(status)
+}
+
+/// 🔑 Whether (and where) a Deepgram API key is configured; the key itself is masked
+#[tauri::command]
+fn get_api_key_status() -> deepgram::ApiKeyStatus {
+    deepgram::api_key_status()
+}
+
+/// 💾 Remember a device that recording successfully started on
+fn remember_device(device: &str) {
+    if device.trim().is_empty() {
+        return;
+    }
+    let device = device.to_string();
+    if let Err(e) = config::update(|s| s.preferred_device = Some(device)) {
+        eprintln!("⚠️ Failed to persist preferred device: {}", e);
+    }
 }
 
 /// 🎙️ Start recording from selected mic
+///
+/// `meter_channels` (default 1) sets how many input channels the level meter
+/// reports; more than one emits `audio_meter_multi` instead of `audio_meter`.
+/// `options` carries per-recording transcription settings (see `RecordingOptions`).
+/// `wav_path`, if given, also saves the captured audio there (finalized by `stop_recording`).
+/// `sample_rate` asks for a capture rate (e.g. 16000 to skip resampling); devices that
+/// can't do it use their default, and `RecordingInfo::sample_rate` tells which was used.
+/// Fails without starting anything if a recording is already in progress, the options
+/// are invalid, the API key is missing, or no capture path could be opened.
 #[tauri::command]
-fn start_recording(app: AppHandle, device: String) {
+fn start_recording(
+    app: AppHandle,
+    device: String,
+    meter_channels: Option<u16>,
+    options: Option<options::RecordingOptions>,
+    wav_path: Option<String>,
+    sample_rate: Option<u32>,
+) -> Result<session::RecordingInfo, String> {
+    // A second start would replace the running session's channel and worker, orphaning them
+    if session::state().is_active() {
+        return Err("A recording is already in progress".into());
+    }
+    let requested_rate = sample_rate;
+    let options = options.unwrap_or_default();
+    if let Err(e) = options.validate() {
+        eprintln!("❌ Invalid recording options: {}", e);
+        let _ = app.emit("error", e.clone());
+        return Err(e);
+    }
+    let backend = backend::select(options.backend.as_deref())
+        .and_then(|b| match b.supports_streaming() {
+            true => Ok(b),
+            false => Err(format!("The {} backend only transcribes files", b.name())),
+        })
+        .inspect_err(|e| {
+            eprintln!("❌ {}", e);
+            let _ = app.emit("error", e.clone());
+        })?;
+    println!("🔌 Transcription backend: {}", backend.name());
+
+    // Empty device → use the remembered one, if it's still around
+    let mut device = device;
+    if device.trim().is_empty() {
+        if let Some(preferred) = config::load().preferred_device {
+            if audio::list_input_devices().contains(&preferred) {
+                device = preferred;
+            } else {
+                println!("⚠️ Preferred device {:?} missing, using default", preferred);
+                let _ = app.emit("preferred_device_missing", preferred);
+            }
+        }
+    }
+
     println!("🎙️ Recording started using device: {}", device);
+    *ACTIVE_DEVICE.lock().unwrap() = Some(device.clone());
+    session::set_state(session::SessionState::Recording);
+    session::mark_started();
 
-    let (tx, rx) = mpsc::unbounded_channel::<Vec<i16>>();
+    transcript::clear();
+    audio::gain::configure(options.gain, options.auto_gain);
+
+    let (tx, rx) = audio::queue::channel(app.clone());
 
     {
         let mut guard = AUDIO_TX.lock().unwrap();
@@ -38,244 +209,883 @@ fn start_recording(app: AppHandle, device: String) {
 
     // Try to spawn the helper audio worker process which writes framed i16 PCM to stdout.
     // If that fails, fall back to the in-process CPAL stream.
+    let worker_path = worker::worker_path();
+    let header_timeout = Duration::from_millis(config::load().worker_header_timeout_ms);
 
-    // Helper to locate worker binary next to the current exe.
-    fn worker_path_name() -> std::path::PathBuf {
-        let worker_name = if cfg!(windows) { "audio_worker.exe" } else { "audio_worker" };
-        if let Ok(p) = std::env::current_exe() {
-            if let Some(dir) = p.parent() {
-                let cand = dir.join(worker_name);
-                if cand.exists() {
-                    return cand;
-                }
-            }
-        }
-        // fallback to just the name (assume in PATH)
-        std::path::PathBuf::from(worker_name)
-    }
-
-    let worker_path = worker_path_name();
-
-    // Attempt to spawn worker with --device <name>
-    let spawn_result = Command::new(&worker_path)
-        .arg("--device")
-        .arg(&device)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .spawn();
-
-    if let Ok(mut child) = spawn_result {
-        // read header (magic + sample_rate)
-        if let Some(mut out) = child.stdout.take() {
-            // blocking read for header
-            let mut header = [0u8; 8];
-            match out.read_exact(&mut header) {
-                Ok(_) => {
-                    if &header[0..4] != b"SRAT" {
-                        eprintln!("audio_worker sent invalid header");
-                    }
-                    let sr = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
-                    let sample_rate = if sr == 0 { 16000 } else { sr };
-
-                    println!("🔌 Spawned audio_worker (pid={}) sample_rate={}", child.id(), sample_rate);
-
-                    // Spawn Deepgram streaming task with the received sample_rate
-                    tauri::async_runtime::spawn(async move {
-                        println!("🧵 Deepgram async task started (worker mode)");
-                        deepgram::stream_to_deepgram(rx, app, sample_rate).await;
-                        println!("🧵 Deepgram async task ended (worker mode)");
-                    });
-
-                    // Move tx clone into a blocking thread that reads frames and forwards
-                    let forwarding_sender = tx.clone();
-                    thread::spawn(move || {
-                        let mut reader = out;
-                        loop {
-                            // read frame length (u32 LE)
-                            let mut lenb = [0u8; 4];
-                            if let Err(e) = reader.read_exact(&mut lenb) {
-                                eprintln!("audio_worker read error (len): {}", e);
-                                break;
-                            }
-                            let len = u32::from_le_bytes(lenb) as usize;
-                            let mut buf = vec![0u8; len * 2];
-                            if let Err(e) = reader.read_exact(&mut buf) {
-                                eprintln!("audio_worker read error (payload): {}", e);
-                                break;
-                            }
-                            // convert to i16 samples
-                            let mut samples = Vec::with_capacity(len);
-                            for i in 0..len {
-                                let lo = buf[i * 2];
-                                let hi = buf[i * 2 + 1];
-                                samples.push(i16::from_le_bytes([lo, hi]));
-                            }
-
-                            // send to channel
-                            if forwarding_sender.send(samples).is_err() {
-                                eprintln!("Failed to forward audio frame; receiver closed");
-                                break;
-                            }
-                        }
+    // Ask for the rate the backend sends at, so no resampling is needed
+    let wanted = worker::WorkerFormat {
+        sample_rate: requested_rate.or(options.send_rate).unwrap_or(16000),
+        channels: 1,
+    };
 
-                        // if we exit loop, ensure child is killed
-                        let _ = child.kill();
-                    });
-                    return;
-                }
-                Err(e) => {
-                    eprintln!("Failed to read header from audio_worker: {}", e);
+    // The worker is only asked for mono; stereo capture always runs in-process
+    if options.multichannel {
+        println!("🎚️ Stereo capture requested: using the in-process mic stream");
+    } else {
+        // Capture from the same host the in-process stream would use
+        let host = audio::selected_host_id();
+        match worker::spawn(&worker_path, &host, &device, wanted) {
+            Ok(mut child) => {
+                // read header (magic + sample_rate)
+                if let Some(out) = child.stdout.take() {
+                    // read header, but don't let a hung worker block start_recording
+                    match worker::read_header_timeout(out, header_timeout) {
+                        Ok((out, format)) => {
+                            let sample_rate = format.sample_rate;
+                            println!(
+                                "🔌 Spawned audio_worker (pid={}) sample_rate={} channels={}",
+                                child.id(),
+                                sample_rate,
+                                format.channels
+                            );
+                            remember_device(&device);
+
+                            // Move tx clone into a blocking thread that reads frames and forwards,
+                            // respawning the worker if it crashes mid-session
+                            let forwarding_sender = tx.clone();
+                            let worker_app = app.clone();
+                            let worker_host = host.clone();
+                            let worker_device = device.clone();
+                            let stop = Arc::new(AtomicBool::new(false));
+                            *FORWARD_STOP.lock().unwrap() = Some(stop.clone());
+                            let mut pid = worker::track(child);
+                            // Meter like the in-process stream does (gain first, pauses decay)
+                            let decimation = config::load().meter_decimation;
+                            let mut frame_meter = audio::meter::FrameMeter::new(Some(app.clone()), format.channels as usize, decimation);
+                            let mut process = move |samples: &mut [i16]| {
+                                if session::is_paused() {
+                                    frame_meter.silence();
+                                    return;
+                                }
+                                audio::gain::apply(samples);
+                                frame_meter.measure(samples);
+                            };
+                            thread::spawn(move || {
+                                let max_respawns = config::load().worker_max_respawns;
+                                let mut reader = out;
+                                let mut respawns = 0;
+                                loop {
+                                    let forwarded = worker::forward_frames(
+                                        &mut reader,
+                                        &forwarding_sender,
+                                        &stop,
+                                        session::paused_flag(),
+                                        &mut process,
+                                    );
+                                    let crashed = match forwarded {
+                                        worker::ForwardEnd::ReceiverClosed | worker::ForwardEnd::Stopped => false,
+                                        worker::ForwardEnd::ProtocolError(e) => {
+                                            // Respawning wouldn't help: this worker binary can't be trusted
+                                            eprintln!("❌ audio_worker protocol error: {}", e);
+                                            worker::kill(Some(pid));
+                                            if !stop.load(Ordering::Relaxed) {
+                                                fall_back_to_mic_stream(&worker_app, &worker_device, format);
+                                            }
+                                            break;
+                                        }
+                                        worker::ForwardEnd::ReadError => {
+                                            // A clean exit (status 0) or a stopped session is not a crash
+                                            !worker::exited_cleanly(pid) && !stop.load(Ordering::Relaxed)
+                                        }
+                                    };
+
+                                    // if we exit loop, ensure child is killed (unless a newer session owns the slot)
+                                    worker::kill(Some(pid));
+
+                                    if !crashed {
+                                        break;
+                                    }
+                                    if respawns >= max_respawns {
+                                        eprintln!("❌ audio_worker crashed; giving up after {} respawns", respawns);
+                                        break;
+                                    }
+                                    respawns += 1;
+
+                                    match worker::start(&worker_path, &worker_host, &worker_device, wanted, header_timeout) {
+                                        Ok((new_child, new_out, new_format)) if new_format == format => {
+                                            println!("🔁 Respawned audio_worker (pid={}, attempt {})", new_child.id(), respawns);
+                                            let _ = worker_app.emit("worker_respawned", serde_json::json!({
+                                                "attempt": respawns,
+                                                "pid": new_child.id(),
+                                            }));
+                                            pid = worker::track(new_child);
+                                            reader = new_out;
+                                        }
+                                        Ok((mut new_child, _, new_format)) => {
+                                            eprintln!("❌ Respawned audio_worker changed format ({:?} -> {:?}); stopping", format, new_format);
+                                            let _ = new_child.kill();
+                                            break;
+                                        }
+                                        Err(e) => {
+                                            eprintln!("❌ {}", e);
+                                            break;
+                                        }
+                                    }
+                                }
+                            });
+
+                            start_wav(&app, wav_path.as_deref(), sample_rate, format.channels);
+
+                            // Spawn the transcription task with the received sample_rate
+                            let transcribe = backend.stream(rx, app, sample_rate, format.channels, options);
+                            tauri::async_runtime::spawn(async move {
+                                println!("🧵 Transcription task started (worker mode)");
+                                transcribe.await;
+                                println!("🧵 Transcription task ended (worker mode)");
+                            });
+                            return Ok(session::RecordingInfo {
+                                device,
+                                mode: session::CaptureMode::Worker,
+                                sample_rate,
+                                channels: format.channels,
+                            });
+                        }
+                        Err(worker::HeaderError::Timeout) => {
+                            eprintln!("⏱️ audio_worker sent no header within {:?}", header_timeout);
+                            let _ = child.kill();
+                            let _ = app.emit("worker_header_timeout", header_timeout.as_millis() as u64);
+                        }
+                        Err(worker::HeaderError::Failed(e)) => {
+                            eprintln!("Failed to read header from audio_worker: {}", e);
+                            let _ = child.kill();
+                        }
+                    }
+                } else {
+                    eprintln!("audio_worker spawned without stdout");
                     let _ = child.kill();
                 }
             }
-        } else {
-            eprintln!("audio_worker spawned without stdout");
-            let _ = child.kill();
+            Err(e) => {
+                eprintln!("Failed to spawn audio_worker {:?}: {}", worker_path, e);
+                // Packaged builds should always ship the worker: make a missing one a hard error
+                if config::load().require_worker {
+                    let _ = app.emit("worker_missing", serde_json::json!({
+                        "path": worker_path.to_string_lossy(),
+                        "error": e.to_string(),
+                    }));
+                    abort_start();
+                    return Err(format!("audio_worker not available at {:?}: {}", worker_path, e));
+                }
+            }
         }
-    } else if let Err(e) = spawn_result {
-        eprintln!("Failed to spawn audio_worker {:?}: {}", worker_path, e);
     }
 
     // Fallback: if worker spawn failed or header read failed, use in-process CPAL stream
     println!("↩️ Falling back to in-process mic stream");
-    let sample_rate = audio::start_mic_stream_with_device(device, app.clone(), move |frame| {
-        let guard = AUDIO_TX.lock().unwrap();
-        if let Some(sender) = guard.as_ref() {
-            let _ = sender.send(frame);
+    let started = audio::start_mic_stream_with_device(
+        device.clone(),
+        app.clone(),
+        meter_channels.unwrap_or(1),
+        requested_rate,
+        options.multichannel,
+        send_to_session,
+    );
+    let (sample_rate, channels) = match started {
+        Ok(opened) => opened,
+        Err(e) => {
+            abort_start();
+            let e = format!("Could not open input device {:?}: {}", device, e);
+            let _ = app.emit("error", e.clone());
+            return Err(e);
+        }
+    };
+    remember_device(&device);
+    start_wav(&app, wav_path.as_deref(), sample_rate, channels);
+
+    // Spawn the transcription task (fallback)
+    println!("🚀 Spawning {} task (fallback)", backend.name());
+    let transcribe = backend.stream(rx, app, sample_rate, channels, options);
+    tauri::async_runtime::spawn(async move {
+        println!("🧵 Transcription task started (fallback)");
+        transcribe.await;
+        println!("🧵 Transcription task ended (fallback)");
+    });
+    Ok(session::RecordingInfo { device, mode: session::CaptureMode::Fallback, sample_rate, channels })
+}
+
+/// Forward a captured frame to the current session's transcription task
+fn send_to_session(frame: Vec<i16>) {
+    let guard = AUDIO_TX.lock().unwrap();
+    if let Some(sender) = guard.as_ref() {
+        sender.send(frame);
+    }
+}
+
+/// 🩹 Replace a worker that broke the protocol mid-session with the in-process
+/// stream; the transcription task was set up for `format`, so the stream must match
+fn fall_back_to_mic_stream(app: &AppHandle, device: &str, format: worker::WorkerFormat) {
+    println!("↩️ Switching to the in-process mic stream");
+    let started = audio::start_mic_stream_with_device(
+        device.to_string(),
+        app.clone(),
+        1,
+        Some(format.sample_rate),
+        format.channels > 1,
+        send_to_session,
+    );
+    let reason = match started {
+        Ok((sample_rate, channels)) if sample_rate == format.sample_rate && channels == format.channels => return,
+        Ok((sample_rate, channels)) => {
+            audio::stop_mic_stream();
+            format!("The microphone can't capture {} Hz x{} in-process (got {} Hz x{})", format.sample_rate, format.channels, sample_rate, channels)
         }
+        Err(e) => format!("Could not open input device {:?}: {}", device, e),
+    };
+    eprintln!("❌ {}", reason);
+    let _ = app.emit("recording_stopped", serde_json::json!({ "reason": reason }));
+}
+
+/// 💾 Start the optional WAV copy; failing to open it doesn't stop the recording
+fn start_wav(app: &AppHandle, path: Option<&str>, sample_rate: u32, channels: u16) {
+    let Some(path) = path.filter(|p| !p.trim().is_empty()) else { return };
+    if let Err(e) = recorder::start(std::path::Path::new(path), sample_rate, channels, app.clone()) {
+        eprintln!("❌ {}", e);
+        let _ = app.emit("recording_file_error", serde_json::json!({ "path": path, "error": e }));
+    }
+}
+
+/// 🔑 The Deepgram API key; a missing one is also reported as an `error` event
+fn require_api_key(app: &AppHandle) -> Result<String, String> {
+    deepgram::api_key().inspect_err(|e| {
+        eprintln!("❌ {}", e);
+        let _ = app.emit("error", e.clone());
     })
-    .unwrap_or(16000);
+}
+
+/// Undo the session setup of a `start_recording` that failed part-way
+fn abort_start() {
+    *AUDIO_TX.lock().unwrap() = None;
+    *ACTIVE_DEVICE.lock().unwrap() = None;
+    session::set_state(session::SessionState::Idle);
+}
+
+/// 🧪 Check that Deepgram accepts an option set, without recording anything
+#[tauri::command]
+async fn test_deepgram_options(options: Option<options::RecordingOptions>) -> deepgram::OptionsCheck {
+    deepgram::test_options(options.unwrap_or_default()).await
+}
+
+/// 🔑 Whether a Deepgram API key is configured (lets the UI gate the record button)
+#[tauri::command]
+fn has_api_key() -> bool {
+    deepgram::api_key().is_ok()
+}
+
+/// 🎛️ Sample rates, channel counts and sample formats the named input device supports
+#[tauri::command]
+fn get_device_capabilities(device: String) -> Result<audio::DeviceCaps, String> {
+    audio::device_capabilities(&device)
+}
+
+/// 📊 Latest input level (0.0..=1.0), for polling instead of the ~20 Hz `audio_level` event
+#[tauri::command]
+fn get_audio_level() -> f32 {
+    audio::meter::level()
+}
+
+/// 🎚️ Tune the level meter: `attack` while it rises, `release` while it falls
+/// (each in `0.0 < x <= 1.0`; 1.0 follows the raw level)
+#[tauri::command]
+fn set_meter_smoothing(attack: f32, release: f32) -> Result<(), String> {
+    audio::meter::set_smoothing(attack, release)
+}
+
+/// 🌐 Audio hosts that can be passed to `select_host`
+#[tauri::command]
+fn list_audio_hosts() -> Vec<String> {
+    audio::list_hosts()
+}
+
+/// 🌐 Capture from the named host (e.g. `Alsa`) from the next recording on;
+/// an empty name goes back to preferring PulseAudio
+#[tauri::command]
+fn select_host(name: String) -> Result<(), String> {
+    audio::set_host(&name)
+}
+
+/// 🩺 Which audio host was selected (and why), with its default device and device count
+#[tauri::command]
+fn audio_backend_info() -> audio::BackendInfo {
+    audio::backend_info()
+}
+
+/// 🎚️ Capture a short sample for mic calibration (no Deepgram involved)
+#[tauri::command]
+async fn capture_sample(device: Option<String>, secs: f32) -> Result<audio::meter::SampleStats, String> {
+    if AUDIO_TX.lock().unwrap().is_some() {
+        return Err("Cannot capture a sample while recording".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || audio::capture_sample(device, secs))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// ⚠️ Turn a Deepgram error object into a `transcription_error` event and an `Err`
+fn check_transcription_error(app: &AppHandle, json: &serde_json::Value) -> Result<(), String> {
+    match deepgram::TranscriptionError::from_response(json) {
+        Some(err) => {
+            eprintln!("❌ Deepgram error: {}", err.message);
+            let message = err.message.clone();
+            let _ = app.emit("transcription_error", err);
+            Err(message)
+        }
+        None => Ok(()),
+    }
+}
+
+/// 📦 Transcribe audio already held in memory (e.g. from the webview) without touching disk
+#[tauri::command]
+async fn transcribe_bytes(
+    app: AppHandle,
+    bytes: Vec<u8>,
+    mime: String,
+    extra_params: Option<Vec<(String, String)>>,
+) -> Result<String, String> {
+    let max = config::load().max_upload_bytes;
+    if bytes.len() as u64 > max {
+        return Err(format!("Audio is too large ({} bytes, limit {} bytes)", bytes.len(), max));
+    }
+    if !(mime.starts_with("audio/") || mime.starts_with("video/")) {
+        return Err(format!("Unsupported MIME type: {}", mime));
+    }
+
+    require_api_key(&app)?;
 
-    // Spawn Deepgram streaming task (fallback)
-    println!("🚀 Spawning Deepgram task (fallback)");
+    println!("🚀 Transcribing {} bytes of {}", bytes.len(), mime);
+    let json = deepgram::transcribe_prerecorded(bytes, Some(&mime), &[], &extra_params.unwrap_or_default()).await?;
+    check_transcription_error(&app, &json)?;
+
+    match model::TranscriptEvent::from_result(&json) {
+        Some(event) => {
+            let text = event.text.clone();
+            transcript::emit(&app, event, config::load().legacy_transcript_event);
+            Ok(text)
+        }
+        None => {
+            println!("⚠️ Empty transcript");
+            Ok(String::new())
+        }
+    }
+}
+
+/// 🎚️ Transcribe a multi-track file (e.g. host/guest on left/right) channel by channel
+///
+/// Emits `channel_transcript` per channel and returns the interleaved, time-ordered
+/// conversation (segments labelled with their channel).
+#[tauri::command]
+async fn transcribe_file_channels(
+    app: AppHandle,
+    path: String,
+    extra_params: Option<Vec<(String, String)>>,
+) -> Result<Vec<model::Segment>, String> {
+    require_api_key(&app)?;
+    let mime = media::audio_mime(std::path::Path::new(&path))?;
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let extra = extra_params.unwrap_or_default();
+    let json = deepgram::transcribe_prerecorded(bytes, mime, &[("multichannel", "true")], &extra).await?;
+    check_transcription_error(&app, &json)?;
+
+    let channels = model::ChannelTranscript::parse_all(&json);
+    for c in &channels {
+        let _ = app.emit("channel_transcript", c);
+    }
+
+    Ok(model::interleave_channels(&channels))
+}
+
+/// 📂 Pick an audio file and transcribe it (progress arrives as `file_transcribe_*` events).
+///
+/// `backend` picks the provider for this file (`None` → the default); `options`
+/// default to smart formatting with paragraphs. Returns the transcript, or `None`
+/// if the dialog was cancelled.
+#[tauri::command]
+async fn transcribe_file_cmd(
+    app: AppHandle,
+    backend: Option<String>,
+    options: Option<options::FileOptions>,
+) -> Result<Option<model::FileTranscript>, String> {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title("Transcribe Audio File")
+        .add_filter("Audio", file_transcribe::AUDIO_EXTENSIONS)
+        .pick_file(move |path| {
+            let _ = tx.send(path.and_then(|f| f.as_path().map(|p| p.to_path_buf())));
+        });
+
+    let Some(path) = rx.await.map_err(|_| "File dialog closed unexpectedly".to_string())? else {
+        return Ok(None);
+    };
+    file_transcribe::transcribe_file(path, app, backend, options.unwrap_or_default()).await.map(Some)
+}
+
+/// 📼 Stream a 16-bit PCM WAV file through the live transcription path, as if it
+/// were a mic: resampled to 16 kHz mono and paced at `speed` × real time (default 1,
+/// up to 20). Emits the same events as a recording; `stop_recording`,
+/// `pause_recording` and `resume_recording` apply. Resolves once the whole file
+/// was sent, with its length in seconds.
+#[tauri::command]
+async fn stream_wav_file(
+    app: AppHandle,
+    path: String,
+    speed: Option<f32>,
+    options: Option<options::RecordingOptions>,
+) -> Result<f64, String> {
+    let speed = speed.unwrap_or(1.0);
+    wav_stream::validate_speed(speed)?;
+    let options = options.unwrap_or_default();
+    options.validate()?;
+    if session::state().is_active() {
+        return Err("Stop the current recording before streaming a file".into());
+    }
+    let backend = backend::select(options.backend.as_deref())?;
+    if !backend.supports_streaming() {
+        return Err(format!("The {} backend only transcribes files", backend.name()));
+    }
+
+    let (tx, rx) = audio::queue::channel(app.clone());
+    *AUDIO_TX.lock().unwrap() = Some(tx.clone());
+    session::set_state(session::SessionState::Recording);
+    session::mark_started();
+    transcript::clear();
+
+    println!("🚀 Spawning {} task (WAV stream)", backend.name());
+    let transcribe = backend.stream(rx, app, wav_stream::STREAM_RATE, 1, options);
     tauri::async_runtime::spawn(async move {
-        println!("🧵 Deepgram async task started (fallback)");
-        deepgram::stream_to_deepgram(rx, app, sample_rate).await;
-        println!("🧵 Deepgram async task ended (fallback)");
+        transcribe.await;
+        println!("🧵 Transcription task ended (WAV stream)");
     });
+
+    let fed = wav_stream::feed(std::path::Path::new(&path), tx, speed).await;
+    // Closing the channel lets the backend flush its final results
+    if session::state().is_active() {
+        end_session();
+    }
+    fed
+}
+
+/// 🚦 Current session state
+#[tauri::command]
+fn get_session_state() -> session::SessionState {
+    session::state()
 }
 
 /// 🛑 Stop recording
+///
+/// Capture keeps running for the configured post-roll first, so a last word
+/// spoken right as the button is pressed still reaches Deepgram.
 #[tauri::command]
-fn stop_recording() {
+async fn stop_recording() {
+    let post_roll = Duration::from_millis(config::load().post_roll_ms);
+    if !post_roll.is_zero() && AUDIO_TX.lock().unwrap().is_some() {
+        println!("⏳ Post-roll: capturing {:?} more", post_roll);
+        tokio::time::sleep(post_roll).await;
+    }
     println!("🛑 Recording stopped");
+    end_session();
+}
 
+/// Tear down capture right away: the worker and mic stream stop, and closing the
+/// audio channel tells the transcription task to finish
+fn end_session() {
     {
         let mut guard = AUDIO_TX.lock().unwrap();
         *guard = None;
     }
+    // Wake the forwarding thread so it exits instead of lingering on the worker's stdout
+    if let Some(stop) = FORWARD_STOP.lock().unwrap().take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    worker::kill(None);
+    *ACTIVE_DEVICE.lock().unwrap() = None;
+    session::set_state(session::SessionState::Idle);
 
     audio::stop_mic_stream();
+    // Frames still in flight to the Deepgram task are not written after this
+    let _ = recorder::finish();
+    // A clean stop: the auto-saved session file is not a crash leftover
+    if let Err(e) = history::end_session() {
+        eprintln!("⚠️ Could not close history session: {}", e);
+    }
 }
 
-/// 📄 Export transcript as TXT
+/// ⏸️ Pause transcription: the mic and Deepgram connection stay open, audio is dropped
 #[tauri::command]
-async fn export_txt(app: AppHandle, transcript: String) -> Result<(), String> {
-    app.dialog()
-        .file()
-        .set_title("Export Transcript (.txt)")
-        .add_filter("Text File", &["txt"])
-        .save_file(move |path| {
-            if let Some(p) = path.and_then(|f| f.as_path().map(|p| p.to_path_buf())) {
-                let _ = fs::write(p, transcript);
-            }
-        });
+fn pause_recording(app: AppHandle) -> Result<(), String> {
+    if session::state() != session::SessionState::Recording {
+        return Err("Not recording".into());
+    }
+    session::set_state(session::SessionState::Paused);
+    println!("⏸️ Recording paused");
+    let _ = app.emit("recording_paused", true);
+    Ok(())
+}
 
+/// ▶️ Resume a paused recording
+#[tauri::command]
+fn resume_recording(app: AppHandle) -> Result<(), String> {
+    if session::state() != session::SessionState::Paused {
+        return Err("Recording is not paused".into());
+    }
+    session::set_state(session::SessionState::Recording);
+    println!("▶️ Recording resumed");
+    let _ = app.emit("recording_paused", false);
     Ok(())
 }
 
-/// 📄 Export transcript as Markdown
+/// ⏱️ Duration of an audio file in seconds (read from headers, cached)
+#[tauri::command]
+fn audio_duration_secs(path: String) -> Result<f64, String> {
+    media::audio_duration_secs(std::path::Path::new(&path))
+}
+
+/// 🗣️ Speaking rate over the current session's finalized words
+#[tauri::command]
+fn speaking_rate() -> analysis::SpeakingRate {
+    analysis::speaking_rate(&transcript::words())
+}
+
+/// 🔁 Re-emit the session's finalized transcript for a freshly mounted UI
+#[tauri::command]
+fn replay_current_transcript(app: AppHandle) -> Result<(), String> {
+    app.emit("transcript_complete", transcript::snapshot()).map_err(|e| e.to_string())
+}
+
+/// 📜 The session transcript held in Rust (text, segments, words)
+#[tauri::command]
+fn get_transcript() -> transcript::Snapshot {
+    transcript::snapshot()
+}
+
+/// 🧹 Empty the session transcript (exports then have nothing to write)
+#[tauri::command]
+fn clear_transcript(app: AppHandle) {
+    transcript::clear();
+    println!("🧹 Transcript cleared");
+    emit_transcript_edited(&app);
+}
+
+/// 📋 Summarize the session transcript as bullet points (one per summary sentence).
+///
+/// Also emitted as `summary`. Fails when there is nothing transcribed yet.
 #[tauri::command]
-async fn export_md(app: AppHandle, transcript: String) -> Result<(), String> {
-    let content = format!("# Transcript\n\n{}", transcript);
+async fn summarize_session(app: AppHandle, language: Option<String>) -> Result<Vec<String>, String> {
+    let text = transcript::full_text();
+    if text.trim().is_empty() {
+        return Err("Nothing to summarize: the transcript is empty".into());
+    }
+    require_api_key(&app)?;
+    let language = language.filter(|l| !l.trim().is_empty()).unwrap_or_else(|| options::DEFAULT_LANGUAGE.to_string());
+
+    println!("📋 Summarizing {} characters", text.len());
+    let summary = deepgram::summarize_text(&text, &language).await?;
+    let _ = app.emit("summary", serde_json::json!({ "text": summary }));
+    Ok(export::split_sentences(&summary))
+}
 
+/// 💾 Show a save dialog, wait for the user's choice and write `content` to the chosen file.
+///
+/// Returns the written path, or `None` if the dialog was cancelled. The outcome is
+/// also emitted as `export_result`.
+async fn save_via_dialog(
+    app: &AppHandle,
+    title: &str,
+    filter_name: &str,
+    extensions: &[&str],
+    content: Vec<u8>,
+) -> Result<Option<String>, String> {
+    let (tx, rx) = oneshot::channel();
     app.dialog()
         .file()
-        .set_title("Export Transcript (.md)")
-        .add_filter("Markdown", &["md"])
+        .set_title(title)
+        .add_filter(filter_name, extensions)
         .save_file(move |path| {
-            if let Some(p) = path.and_then(|f| f.as_path().map(|p| p.to_path_buf())) {
-                let _ = fs::write(p, content);
-            }
+            let _ = tx.send(path.and_then(|f| f.as_path().map(|p| p.to_path_buf())));
         });
 
+    let chosen = rx.await.map_err(|_| "Save dialog closed unexpectedly".to_string())?;
+    let (result, outcome) = match chosen {
+        Some(p) => match fs::write(&p, content) {
+            Ok(()) => (Ok(Some(p.to_string_lossy().to_string())), export::ExportResult::saved(&p)),
+            Err(e) => (
+                Err(format!("Failed to write {}: {}", p.display(), e)),
+                export::ExportResult::failed(&p, e.to_string()),
+            ),
+        },
+        None => (Ok(None), export::ExportResult::cancelled()),
+    };
+    let _ = app.emit("export_result", outcome);
+    result
+}
+
+/// ✏️ Tell the UI the authoritative transcript changed
+fn emit_transcript_edited(app: &AppHandle) {
+    let _ = app.emit("transcript_edited", serde_json::json!({
+        "text": transcript::full_text(),
+        "words": transcript::words(),
+    }));
+}
+
+/// ✏️ Replace the text of one word in the session transcript
+#[tauri::command]
+fn update_word(app: AppHandle, index: usize, text: String) -> Result<(), String> {
+    transcript::update_word(index, text)?;
+    emit_transcript_edited(&app);
+    Ok(())
+}
+
+/// ✏️ Remove one word from the session transcript
+#[tauri::command]
+fn delete_word(app: AppHandle, index: usize) -> Result<(), String> {
+    transcript::delete_word(index)?;
+    emit_transcript_edited(&app);
     Ok(())
 }
 
-/// 📄 Export transcript as SRT
+/// ✏️ Insert a word into the session transcript before `index`
 #[tauri::command]
-async fn export_srt(app: AppHandle, transcript: String) -> Result<(), String> {
-    // naive sentence split
-    let parts: Vec<&str> = transcript.split(". ").collect();
-    let mut srt = String::new();
-    let mut time: u64 = 0;
-    for (i, p) in parts.iter().enumerate() {
-        let start = time;
-        let end = time + 5; // 5s per chunk
-        let idx = i + 1;
-        let start_ts = format!("{:02}:{:02}:{:02},000", start / 3600, (start % 3600) / 60, start % 60);
-        let end_ts = format!("{:02}:{:02}:{:02},000", end / 3600, (end % 3600) / 60, end % 60);
-        srt.push_str(&format!("{}\n{} --> {}\n{}\n\n", idx, start_ts, end_ts, p.trim()));
-        time = end;
+fn insert_word(app: AppHandle, index: usize, word: model::Word) -> Result<(), String> {
+    transcript::insert_word(index, word)?;
+    emit_transcript_edited(&app);
+    Ok(())
+}
+
+/// 📄 Export transcript as TXT
+///
+/// Like every export, it writes the session transcript unless `transcript` /
+/// `segments` are passed. With `with_timestamps`, each segment (the given `segments`, else the current
+/// session's) is prefixed with `[mm:ss]`; without segment data the flat text is kept.
+/// `wrap_width` hard-wraps lines at word boundaries.
+#[tauri::command]
+async fn export_txt(
+    app: AppHandle,
+    transcript: Option<String>,
+    with_timestamps: Option<bool>,
+    segments: Option<Vec<model::Segment>>,
+    wrap_width: Option<usize>,
+) -> Result<Option<String>, String> {
+    let mut content = transcript.unwrap_or_else(transcript::full_text);
+    if with_timestamps.unwrap_or(false) {
+        let segments = segments.unwrap_or_else(transcript::segments);
+        if segments.is_empty() {
+            eprintln!("⚠️ No segment data for timestamps; exporting plain text");
+        } else {
+            content = export::timestamped_text(&segments);
+        }
+    }
+    if let Some(width) = wrap_width {
+        content = export::wrap_text(&content, width);
     }
 
-    app.dialog()
-        .file()
-        .set_title("Export Transcript (.srt)")
-        .add_filter("SRT", &["srt"])
-        .save_file(move |path| {
-            if let Some(p) = path.and_then(|f| f.as_path().map(|p| p.to_path_buf())) {
-                let _ = fs::write(p, srt.clone());
-            }
-        });
+    save_via_dialog(&app, "Export Transcript (.txt)", "Text File", &["txt"], content.into_bytes()).await
+}
 
+/// 📄 Export transcript as Markdown (optionally hard-wrapped at `wrap_width`)
+#[tauri::command]
+async fn export_md(app: AppHandle, transcript: Option<String>, wrap_width: Option<usize>) -> Result<Option<String>, String> {
+    let transcript = transcript.unwrap_or_else(transcript::full_text);
+    let body = match wrap_width {
+        Some(width) => export::wrap_text(&transcript, width),
+        None => transcript,
+    };
+    let content = export::markdown(&body);
+
+    save_via_dialog(&app, "Export Transcript (.md)", "Markdown", &["md"], content.into_bytes()).await
+}
+
+/// 📋 Put the transcript on the system clipboard, optionally as the Markdown `export_md` writes
+#[tauri::command]
+fn copy_to_clipboard(app: AppHandle, text: String, markdown: Option<bool>) -> Result<(), String> {
+    let content = if markdown.unwrap_or(false) { export::markdown(&text) } else { text };
+    app.clipboard()
+        .write_text(content)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    println!("📋 Transcript copied to clipboard");
     Ok(())
 }
 
-/// 📄 Export transcript as VTT
+/// 📄 Export transcript as a Word document (speaker labels in bold when diarized)
+#[tauri::command]
+async fn export_docx(
+    app: AppHandle,
+    transcript: Option<String>,
+    segments: Option<Vec<model::Segment>>,
+) -> Result<Option<String>, String> {
+    let segments = segments.unwrap_or_else(transcript::segments);
+    let transcript = transcript.unwrap_or_else(transcript::full_text);
+    let content = export::docx("Transcript", &export::doc_paragraphs(&transcript, &segments))?;
+
+    save_via_dialog(&app, "Export Transcript (.docx)", "Word Document", &["docx"], content).await
+}
+
+/// 📄 Export transcript as PDF (wrapped at `wrap_width` columns, paginated)
 #[tauri::command]
-async fn export_vtt(app: AppHandle, transcript: String) -> Result<(), String> {
-    let parts: Vec<&str> = transcript.split(". ").collect();
+async fn export_pdf(app: AppHandle, transcript: Option<String>, wrap_width: Option<usize>) -> Result<Option<String>, String> {
+    let transcript = transcript.unwrap_or_else(transcript::full_text);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let width = wrap_width.unwrap_or(export::PDF_DEFAULT_WRAP);
+    let content = export::pdf(&transcript, width, &export::utc_date(now))?;
+
+    save_via_dialog(&app, "Export Transcript (.pdf)", "PDF", &["pdf"], content).await
+}
+
+/// 🧾 Export the full structured transcript (segments, words, speakers) as JSON
+///
+/// Defaults to the current session's segments and words.
+#[tauri::command]
+async fn export_json(
+    app: AppHandle,
+    segments: Option<Vec<model::Segment>>,
+    words: Option<Vec<model::Word>>,
+) -> Result<Option<String>, String> {
+    let segments = segments.unwrap_or_else(transcript::segments);
+    let words = words.unwrap_or_else(transcript::words);
+    let doc = export::transcript_json(&segments, &words);
+    let content = serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+
+    save_via_dialog(&app, "Export Transcript (.json)", "JSON", &["json"], content.into_bytes()).await
+}
+
+/// 📊 Export transcript as CSV, one row per segment (a single row for a plain transcript)
+#[tauri::command]
+async fn export_csv(
+    app: AppHandle,
+    transcript: Option<String>,
+    segments: Option<Vec<model::Segment>>,
+    words: Option<Vec<model::Word>>,
+) -> Result<Option<String>, String> {
+    let segments = segments.unwrap_or_else(transcript::segments);
+    let words = words.unwrap_or_else(transcript::words);
+    let text = transcript.unwrap_or_else(transcript::full_text);
+    let content = export::csv(&segments, &words, &text)?;
+
+    save_via_dialog(&app, "Export Transcript (.csv)", "CSV", &["csv"], content).await
+}
+
+/// Cues for the subtitle exports: real segment timings (the given `segments`, else
+/// the session's unless a plain `transcript` is passed), otherwise the text timed
+/// from VAD spans (or 5s per sentence); then split to fit `limits` (default: 2
+/// lines of 42 characters, 6s)
+fn subtitle_cues(
+    transcript: Option<String>,
+    segments: Option<Vec<model::Segment>>,
+    limits: Option<export::CueLimits>,
+) -> Vec<model::Segment> {
+    let segments = match (segments, &transcript) {
+        (None, None) => Some(transcript::segments()),
+        (segments, _) => segments,
+    };
+    let cues = match segments.filter(|s| !s.is_empty()) {
+        Some(mut segments) => {
+            segments.retain(|s| !s.text.trim().is_empty());
+            segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+            segments
+        }
+        None => export::caption_cues(&transcript.unwrap_or_default(), &transcript::speech_spans()),
+    };
+    export::limit_cues(&cues, limits.unwrap_or_default())
+}
+
+/// 📄 Export transcript as SRT
+///
+/// Pass `segments` (start/end seconds + text) for accurate timing; a plain
+/// `transcript` string falls back to VAD speech spans or a fixed 5s per sentence.
+/// `limits` caps characters per line, lines and seconds per cue.
+#[tauri::command]
+async fn export_srt(
+    app: AppHandle,
+    transcript: Option<String>,
+    segments: Option<Vec<model::Segment>>,
+    limits: Option<export::CueLimits>,
+) -> Result<Option<String>, String> {
+    let mut srt = String::new();
+    for (i, cue) in subtitle_cues(transcript, segments, limits).iter().enumerate() {
+        let start_ts = export::format_timestamp(cue.start, export::TimestampStyle::Srt);
+        let end_ts = export::format_timestamp(cue.end, export::TimestampStyle::Srt);
+        srt.push_str(&format!("{}\n{} --> {}\n{}\n\n", i + 1, start_ts, end_ts, cue.text.trim()));
+    }
+
+    save_via_dialog(&app, "Export Transcript (.srt)", "SRT", &["srt"], srt.into_bytes()).await
+}
+
+/// 📄 Export transcript as VTT (same inputs and timing as `export_srt`)
+#[tauri::command]
+async fn export_vtt(
+    app: AppHandle,
+    transcript: Option<String>,
+    segments: Option<Vec<model::Segment>>,
+    limits: Option<export::CueLimits>,
+) -> Result<Option<String>, String> {
     let mut vtt = String::from("WEBVTT\n\n");
-    let mut time: u64 = 0;
-    for p in parts.iter() {
-        let start = time;
-        let end = time + 5;
-        let start_ts = format!("{:02}:{:02}:{:02}.000", start / 3600, (start % 3600) / 60, start % 60);
-        let end_ts = format!("{:02}:{:02}:{:02}.000", end / 3600, (end % 3600) / 60, end % 60);
-        vtt.push_str(&format!("{} --> {}\n{}\n\n", start_ts, end_ts, p.trim()));
-        time = end;
+    for cue in subtitle_cues(transcript, segments, limits) {
+        let start_ts = export::format_timestamp(cue.start, export::TimestampStyle::Vtt);
+        let end_ts = export::format_timestamp(cue.end, export::TimestampStyle::Vtt);
+        vtt.push_str(&format!("{} --> {}\n{}\n\n", start_ts, end_ts, cue.text.trim()));
     }
 
-    app.dialog()
-        .file()
-        .set_title("Export Transcript (.vtt)")
-        .add_filter("VTT", &["vtt"])
-        .save_file(move |path| {
-            if let Some(p) = path.and_then(|f| f.as_path().map(|p| p.to_path_buf())) {
-                let _ = fs::write(p, vtt.clone());
-            }
-        });
+    save_via_dialog(&app, "Export Transcript (.vtt)", "VTT", &["vtt"], vtt.into_bytes()).await
+}
 
-    Ok(())
+/// 🎞️ Export a web-player timeline (sorted, gap-filled cues + duration) as JSON
+///
+/// Without `segments`, the session's are used (sorted, overlaps merged).
+/// `audio_duration` defaults to the latest segment end.
+#[tauri::command]
+async fn export_timeline_json(
+    app: AppHandle,
+    segments: Option<Vec<model::Segment>>,
+    audio_duration: Option<f64>,
+) -> Result<Option<String>, String> {
+    let timeline = match segments {
+        Some(segments) => export::timeline(&segments, audio_duration.unwrap_or_else(|| export::latest_end(&segments)))?,
+        None => export::session_timeline(transcript::segments(), audio_duration)?,
+    };
+    let content = serde_json::to_string(&timeline).map_err(|e| e.to_string())?;
+
+    save_via_dialog(&app, "Export Timeline (.json)", "JSON", &["json"], content.into_bytes()).await
 }
 
-/// 💾 Save history silently to the app data directory (no dialog)
+/// 🌡️ Export a per-word confidence heatmap as HTML
 #[tauri::command]
-fn save_history_auto(_app: AppHandle, history: Vec<String>) -> Result<String, String> {
-    let content = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+async fn export_confidence_html(app: AppHandle, words: Option<Vec<model::Word>>) -> Result<Option<String>, String> {
+    let words = words.unwrap_or_else(transcript::words);
+    let html = export::confidence_html(&words);
 
-    // fallback to $HOME/.local/share/heard_it if app dir isn't available
-    let dir: PathBuf = match std::env::var("HOME") {
-        Ok(h) => PathBuf::from(h).join(".local/share/heard_it"),
-        Err(_) => return Err("Could not resolve HOME directory".into()),
+    save_via_dialog(&app, "Export Confidence Heatmap (.html)", "HTML", &["html"], html.into_bytes()).await
+}
+
+/// 💾 Save history silently to the app data directory (no dialog)
+///
+/// `language`/`model` are stored as session metadata so `list_history` can filter on them.
+/// One-shot: every call writes a new timestamped file. For repeated saves during a
+/// recording use `begin_session` + `autosave_session`, which keep
+/// rewriting a single file.
+#[tauri::command]
+fn save_history_auto(
+    _app: AppHandle,
+    history: Vec<String>,
+    language: Option<String>,
+    model: Option<String>,
+) -> Result<String, String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_secs();
+    let file = history::HistoryFile {
+        version: history::HISTORY_FORMAT_VERSION,
+        created_at: now,
+        language,
+        model,
+        session_id: None,
+        device: None,
+        ended_at: None,
+        entries: history,
     };
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+
+    let dir = history::history_dir()?;
 
     if let Err(e) = std::fs::create_dir_all(&dir) {
         return Err(format!("Failed to create app dir: {}", e));
     }
 
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_secs();
     let filename = format!("transcript_history_{}.json", now);
     let path = dir.join(filename);
 
@@ -284,44 +1094,239 @@ fn save_history_auto(_app: AppHandle, history: Vec<String>) -> Result<String, St
     Ok(path.to_string_lossy().to_string())
 }
 
+/// 🧷 Start a history session for auto-saving; returns its id
+#[tauri::command]
+fn begin_session(
+    device: Option<String>,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<String, String> {
+    history::begin_session(device, model, language)
+}
+
+/// 💾 Rewrite the current history session's `session_<id>.json` with `history`
+/// (default: the session transcript as a single entry); returns its path
+#[tauri::command]
+fn autosave_session(history: Option<Vec<String>>) -> Result<String, String> {
+    let entries = history.unwrap_or_else(|| vec![transcript::full_text()]);
+    history::save_session(entries).map(|p| p.to_string_lossy().to_string())
+}
+
+/// 🩹 A session that was auto-saved but never stopped (the app crashed mid-recording);
+/// called at startup so the UI can offer to restore it
+#[tauri::command]
+fn check_for_recoverable_session() -> Result<Option<history::RecoverableSession>, String> {
+    history::recoverable_session()
+}
+
+/// 🩹 Answer the recovery prompt: with `restore`, the session's text becomes the
+/// current transcript (returned as the entries); either way it isn't offered again
+#[tauri::command]
+fn resolve_recoverable_session(session_id: String, restore: bool) -> Result<Vec<String>, String> {
+    let entries = if restore {
+        let entries = history::session_entries(&session_id)?;
+        transcript::load_file(&model::FileTranscript { text: entries.join("\n\n"), ..Default::default() });
+        entries
+    } else {
+        Vec::new()
+    };
+    history::close_recovered(&session_id)?;
+    Ok(entries)
+}
+
+/// 📁 Where history files are saved
+#[tauri::command]
+fn get_history_dir() -> Result<String, String> {
+    history::history_dir().map(|p| p.to_string_lossy().to_string())
+}
+
+/// 📁 Save history to `path` from now on (created if missing, must be writable);
+/// an empty `path` goes back to the default. Returns the directory now in use.
+#[tauri::command]
+fn set_history_dir(path: String) -> Result<String, String> {
+    history::set_history_dir(&path).map(|p| p.to_string_lossy().to_string())
+}
+
+/// 🗃️ List auto-saved histories (metadata only), optionally filtered by language/model/date
+#[tauri::command]
+fn list_history(filter: Option<history::HistoryFilter>) -> Result<Vec<history::HistorySummary>, String> {
+    history::list(&filter.unwrap_or_default())
+}
+
+/// 🔎 Search saved transcripts for `query` (case-insensitive; a regex when `regex` is set).
+/// Returns at most `max_hits` matches (default 100), newest files first.
+#[tauri::command]
+async fn search_history(
+    query: String,
+    regex: Option<bool>,
+    max_hits: Option<usize>,
+) -> Result<Vec<history::SearchHit>, String> {
+    let pattern = history::search_pattern(&query, regex.unwrap_or(false))?;
+    let max_hits = max_hits.unwrap_or(history::DEFAULT_MAX_HITS);
+    tauri::async_runtime::spawn_blocking(move || history::search(&pattern, max_hits))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 🗑️ Delete a saved history file (only files inside the history directory)
+#[tauri::command]
+fn delete_history(path: String) -> Result<(), String> {
+    history::delete(&path)
+}
+
+/// 🗑️ Delete all saved history files; returns how many were removed
+#[tauri::command]
+fn delete_all_history() -> Result<usize, String> {
+    history::delete_all()
+}
+
+/// 📂 Paths of saved histories, newest first (empty if none were saved yet)
+#[tauri::command]
+fn list_histories() -> Result<Vec<String>, String> {
+    history::list_paths()
+}
+
+/// 📖 Read a saved history back; without `path` the user picks a file (`None` = cancelled)
+#[tauri::command]
+async fn load_history(app: AppHandle, path: Option<String>) -> Result<Option<Vec<String>>, String> {
+    let path = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            let (tx, rx) = oneshot::channel();
+            let mut dialog = app.dialog().file().set_title("Load Transcript History").add_filter("JSON", &["json"]);
+            if let Ok(dir) = history::history_dir() {
+                dialog = dialog.set_directory(dir);
+            }
+            dialog.pick_file(move |path| {
+                let _ = tx.send(path.and_then(|f| f.as_path().map(|p| p.to_path_buf())));
+            });
+            match rx.await.map_err(|_| "Open dialog closed unexpectedly".to_string())? {
+                Some(p) => p,
+                None => return Ok(None),
+            }
+        }
+    };
+    history::load(&path).map(Some)
+}
+
+/// 👀 Short text preview of a saved history file (by file name, within the history dir)
+#[tauri::command]
+fn preview_history(filename: String, max_chars: usize) -> Result<String, String> {
+    history::preview(&filename, max_chars)
+}
+
 /// 💾 Save transcript history to disk (JSON)
 #[tauri::command]
-async fn save_history(app: AppHandle, history: Vec<String>) -> Result<(), String> {
+async fn save_history(app: AppHandle, history: Vec<String>) -> Result<Option<String>, String> {
     let content = match serde_json::to_string_pretty(&history) {
         Ok(s) => s,
         Err(e) => return Err(format!("Failed to serialize history: {}", e)),
     };
 
-    app.dialog()
-        .file()
-        .set_title("Save Transcript History (.json)")
-        .add_filter("JSON", &["json"])
-        .save_file(move |path| {
-            if let Some(p) = path.and_then(|f| f.as_path().map(|p| p.to_path_buf())) {
-                let _ = fs::write(p, content.clone());
-            }
-        });
+    save_via_dialog(&app, "Save Transcript History (.json)", "JSON", &["json"], content.into_bytes()).await
+}
 
-    Ok(())
+/// 🪟 Release builds on Windows run without a console; `HEARD_IT_CONSOLE=1` opens one
+/// so the log output is visible when reporting bugs
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_console() {
+    if std::env::var("HEARD_IT_CONSOLE").as_deref() == Ok("1") {
+        // Safety: no preconditions; fails harmlessly if a console is already attached
+        unsafe {
+            windows_sys::Win32::System::Console::AllocConsole();
+        }
+    }
 }
 
 /// 🚀 App entry
 fn main() {
     dotenvy::dotenv().ok();
+    #[cfg(all(windows, not(debug_assertions)))]
+    attach_console();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .setup(|app| {
+            match app.path().app_data_dir() {
+                Ok(dir) => history::set_app_data_dir(dir),
+                Err(e) => eprintln!("⚠️ No app data dir ({}); saving history under XDG_DATA_HOME/HOME", e),
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             list_mic_devices,
+            start_device_watcher,
+            stop_device_watcher,
+            get_audio_level,
+            set_meter_smoothing,
+            list_audio_hosts,
+            select_host,
+            audio_backend_info,
+            get_device_capabilities,
+            test_deepgram_options,
+            has_api_key,
+            set_api_key,
+            get_api_key_status,
+            get_preferred_device,
+            get_settings,
+            save_settings,
             start_recording,
             stop_recording,
+            pause_recording,
+            resume_recording,
+            get_session_state,
+            capture_sample,
+            transcribe_bytes,
+            transcribe_file_channels,
+            transcribe_file_cmd,
+            stream_wav_file,
+            audio_duration_secs,
+            speaking_rate,
+            replay_current_transcript,
+            get_transcript,
+            summarize_session,
+            clear_transcript,
+            update_word,
+            delete_word,
+            insert_word,
+            copy_to_clipboard,
             export_txt,
             export_md,
+            export_docx,
+            export_pdf,
+            export_json,
+            export_csv,
             export_srt,
             export_vtt,
+            export_timeline_json,
+            export_confidence_html,
             save_history,
-            save_history_auto
+            save_history_auto,
+            begin_session,
+            autosave_session,
+            check_for_recoverable_session,
+            resolve_recoverable_session,
+            list_history,
+            list_histories,
+            get_history_dir,
+            set_history_dir,
+            search_history,
+            delete_history,
+            delete_all_history,
+            load_history,
+            preview_history
         ])
-        .run(tauri::generate_context!())
-        .expect("❌ error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("❌ error while running tauri application")
+        .run(|_app, event| {
+            // Closing the window mid-recording must not orphan the worker or the mic stream
+            if let tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit = event {
+                if session::state().is_active() {
+                    println!("👋 Exiting during a recording; stopping it");
+                    end_session();
+                }
+                worker::kill(None);
+            }
+        });
 }
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Durations already computed, keyed by path and invalidated by mtime
+static DURATION_CACHE: Mutex<Option<HashMap<PathBuf, (SystemTime, f64)>>> = Mutex::new(None);
+
+/// Audio extensions accepted for upload and the media type sent for them; `None`
+/// leaves the format to Deepgram's auto-detection
+const AUDIO_TYPES: &[(&str, Option<&str>)] = &[
+    ("wav", Some("audio/wav")),
+    ("mp3", Some("audio/mpeg")),
+    ("m4a", Some("audio/mp4")),
+    ("mp4", Some("audio/mp4")),
+    ("aac", Some("audio/aac")),
+    ("flac", Some("audio/flac")),
+    ("ogg", Some("audio/ogg")),
+    ("oga", Some("audio/ogg")),
+    ("opus", Some("audio/ogg")),
+    ("webm", Some("audio/webm")),
+    ("aif", None),
+    ("aiff", None),
+    ("amr", None),
+    ("wma", None),
+];
+
+/// Media type from a file's leading bytes
+fn sniff_mime(head: &[u8]) -> Option<&'static str> {
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WAVE" {
+        return Some("audio/wav");
+    }
+    if head.len() >= 8 && &head[4..8] == b"ftyp" {
+        return Some("audio/mp4");
+    }
+    if head.starts_with(b"fLaC") {
+        Some("audio/flac")
+    } else if head.starts_with(b"OggS") {
+        Some("audio/ogg")
+    } else if head.starts_with(b"ID3") {
+        Some("audio/mpeg")
+    } else if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("audio/webm")
+    } else if head.len() >= 2 && head[0] == 0xFF && head[1] & 0xF6 == 0xF0 {
+        // ADTS sync word, layer 0
+        Some("audio/aac")
+    } else if head.len() >= 2 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0 {
+        // MPEG audio frame sync
+        Some("audio/mpeg")
+    } else {
+        None
+    }
+}
+
+/// 🏷️ Media type to upload `path` with; the file's leading bytes win over its extension.
+///
+/// `Ok(None)` is a known audio format best left to Deepgram's auto-detection; anything
+/// unrecognized is rejected rather than uploaded.
+pub fn audio_mime(path: &Path) -> Result<Option<&'static str>, String> {
+    let mut head = [0u8; 12];
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let n = std::io::Read::read(&mut file, &mut head).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    if let Some(mime) = sniff_mime(&head[..n]) {
+        return Ok(Some(mime));
+    }
+
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match AUDIO_TYPES.iter().find(|(e, _)| *e == ext) {
+        Some((_, mime)) => Ok(*mime),
+        None if ext.is_empty() => Err(format!("Unrecognized audio file: {:?}", path)),
+        None => Err(format!("Unsupported audio file type: .{}", ext)),
+    }
+}
+
+/// ⏱️ Duration of an audio file in seconds, read from container headers where possible.
+///
+/// Formats without a frame count in their headers fall back to walking the packets
+/// (still no decoding). Results are cached per path until the file's mtime changes.
+pub fn audio_duration_secs(path: &Path) -> Result<f64, String> {
+    let mtime = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to stat {:?}: {}", path, e))?;
+
+    if let Some((cached_mtime, secs)) = DURATION_CACHE.lock().unwrap().as_ref().and_then(|c| c.get(path)) {
+        if *cached_mtime == mtime {
+            return Ok(*secs);
+        }
+    }
+
+    let secs = probe_duration(path)?;
+    DURATION_CACHE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(path.to_path_buf(), (mtime, secs));
+    Ok(secs)
+}
+
+fn probe_duration(path: &Path) -> Result<f64, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Unsupported audio file {:?}: {}", path, e))?;
+    let mut format = probed.format;
+
+    let track = format.default_track().ok_or("No audio track found")?;
+    let track_id = track.id;
+    let params = track.codec_params.clone();
+
+    let to_secs = |frames: u64| -> Option<f64> {
+        if let Some(tb) = params.time_base {
+            let t = tb.calc_time(frames);
+            Some(t.seconds as f64 + t.frac)
+        } else {
+            params.sample_rate.map(|rate| frames as f64 / rate as f64)
+        }
+    };
+
+    if let Some(secs) = params.n_frames.and_then(to_secs) {
+        return Ok(secs);
+    }
+
+    // No frame count in the headers: add up packet durations instead
+    let mut frames = 0u64;
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() == track_id {
+            frames += packet.dur;
+        }
+    }
+    to_secs(frames).ok_or_else(|| "Could not determine duration (no sample rate or time base)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_wav(name: &str, samples: usize, rate: u32) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("heard_it_media_{}_{}.wav", name, std::process::id()));
+        crate::wav::write_pcm16_mono(&path, &vec![0i16; samples], rate).unwrap();
+        path
+    }
+
+    #[test]
+    fn wav_duration_from_header() {
+        let path = temp_wav("header", 24000, 16000);
+        let secs = audio_duration_secs(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!((secs - 1.5).abs() < 1e-9, "got {}", secs);
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_mtime() {
+        let path = temp_wav("cache", 8000, 8000);
+        assert!((audio_duration_secs(&path).unwrap() - 1.0).abs() < 1e-9);
+
+        // Same path, new content and a different mtime
+        crate::wav::write_pcm16_mono(&path, &vec![0i16; 16000], 8000).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+        let secs = audio_duration_secs(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!((secs - 2.0).abs() < 1e-9, "got {}", secs);
+    }
+
+    #[test]
+    fn missing_and_invalid_files_are_errors() {
+        assert!(audio_duration_secs(Path::new("/nonexistent/heard_it.wav")).is_err());
+
+        let path = std::env::temp_dir().join(format!("heard_it_media_junk_{}.wav", std::process::id()));
+        std::fs::write(&path, b"definitely not audio").unwrap();
+        let res = audio_duration_secs(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(res.is_err());
+    }
+}
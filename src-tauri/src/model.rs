@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 🔤 A single recognized word with timing (seconds) and confidence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    pub confidence: f64,
+    /// Speaker index when diarization is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    /// Physical audio channel (multichannel transcription)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<usize>,
+}
+
+impl Word {
+    /// Parse a Deepgram `alternatives[n].words` array; missing/invalid entries yield an empty vec.
+    pub fn parse_list(words: &Value) -> Vec<Word> {
+        words
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|w| {
+                        Some(Word {
+                            word: w["punctuated_word"].as_str().or_else(|| w["word"].as_str())?.to_string(),
+                            start: w["start"].as_f64()?,
+                            end: w["end"].as_f64()?,
+                            confidence: w["confidence"].as_f64().unwrap_or(0.0),
+                            speaker: w["speaker"].as_u64().map(|s| s as u32),
+                            channel: None,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 🗣️ Consecutive words by one speaker, emitted as `diarized_transcript`
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakerTurn {
+    pub speaker: u32,
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Group diarized words into speaker turns, splitting wherever the speaker changes
+/// (also mid-utterance). Empty when the words carry no speaker labels.
+pub fn speaker_turns(words: &[Word]) -> Vec<SpeakerTurn> {
+    let mut turns: Vec<SpeakerTurn> = Vec::new();
+    for w in words {
+        let Some(speaker) = w.speaker else { continue };
+        match turns.last_mut() {
+            Some(turn) if turn.speaker == speaker => {
+                turn.text.push(' ');
+                turn.text.push_str(&w.word);
+                turn.end = w.end;
+            }
+            _ => turns.push(SpeakerTurn { speaker, text: w.word.clone(), start: w.start, end: w.end }),
+        }
+    }
+    turns
+}
+
+/// 🧩 A timed span of transcript text (seconds from session start)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<u32>,
+    /// Physical audio channel (multichannel transcription)
+    #[serde(default)]
+    pub channel: Option<usize>,
+}
+
+/// ¶ A paragraph from Deepgram's `paragraphs` feature (`transcript_paragraphs` event)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Paragraph {
+    pub start: f64,
+    pub end: f64,
+    /// The paragraph's sentences joined by spaces
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<u32>,
+}
+
+impl Paragraph {
+    /// Parse `alternative.paragraphs.paragraphs`; empty when Deepgram sent none
+    pub fn parse_list(alternative: &Value) -> Vec<Paragraph> {
+        alternative["paragraphs"]["paragraphs"]
+            .as_array()
+            .map(|paragraphs| {
+                paragraphs
+                    .iter()
+                    .filter_map(|p| {
+                        let text = p["sentences"]
+                            .as_array()?
+                            .iter()
+                            .filter_map(|s| s["text"].as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        Some(Paragraph {
+                            start: p["start"].as_f64()?,
+                            end: p["end"].as_f64()?,
+                            text,
+                            speaker: p["speaker"].as_u64().map(|s| s as u32),
+                        })
+                    })
+                    .filter(|p| !p.text.trim().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Paragraphs as text separated by blank lines
+    pub fn join(paragraphs: &[Paragraph]) -> String {
+        paragraphs.iter().map(|p| p.text.as_str()).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+/// 📝 Payload of the `transcript` event (fields Deepgram didn't provide are `None`)
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptEvent {
+    pub text: String,
+    pub is_final: bool,
+    pub confidence: Option<f64>,
+    /// Seconds from session start
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    pub speaker: Option<u32>,
+    /// Wall-clock seconds since the recording started when the result arrived
+    /// (live sessions only); unlike `start` it's there without word timings
+    pub t_start: Option<f64>,
+}
+
+/// Top alternative of a result: `channel.alternatives[0]` for streaming messages,
+/// `results.channels[0].alternatives[0]` for prerecorded responses
+pub fn top_alternative(json: &Value) -> &Value {
+    let alt = &json["channel"]["alternatives"][0];
+    if alt.is_null() {
+        &json["results"]["channels"][0]["alternatives"][0]
+    } else {
+        alt
+    }
+}
+
+/// 🔤 Payload of the `transcript_words` event
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptWords {
+    pub words: Vec<Word>,
+    /// Confidence of the whole utterance
+    pub confidence: Option<f64>,
+    pub is_final: bool,
+}
+
+impl TranscriptWords {
+    /// Words of a result message (empty when Deepgram sent none)
+    pub fn from_result(json: &Value) -> TranscriptWords {
+        let alt = top_alternative(json);
+        TranscriptWords {
+            words: Word::parse_list(&alt["words"]),
+            confidence: alt["confidence"].as_f64(),
+            is_final: json["is_final"].as_bool().unwrap_or(true),
+        }
+    }
+}
+
+impl TranscriptEvent {
+    /// Build from a streaming result message; `None` when it carries no text
+    pub fn from_result(json: &Value) -> Option<TranscriptEvent> {
+        let alt = top_alternative(json);
+        let text = alt["transcript"].as_str().filter(|t| !t.trim().is_empty())?;
+        let start = json["start"].as_f64();
+        Some(TranscriptEvent {
+            text: text.to_string(),
+            is_final: json["is_final"].as_bool().unwrap_or(true),
+            confidence: alt["confidence"].as_f64(),
+            start,
+            end: start.zip(json["duration"].as_f64()).map(|(s, d)| s + d),
+            speaker: alt["words"][0]["speaker"].as_u64().map(|s| s as u32),
+            t_start: None,
+        })
+    }
+}
+
+/// 🎚️ Transcript of one physical channel, emitted as `channel_transcript`
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelTranscript {
+    pub channel: usize,
+    pub text: String,
+    pub words: Vec<Word>,
+}
+
+impl ChannelTranscript {
+    /// Parse every `results.channels[*]` entry of a Deepgram response
+    pub fn parse_all(json: &Value) -> Vec<ChannelTranscript> {
+        json["results"]["channels"]
+            .as_array()
+            .map(|channels| {
+                channels
+                    .iter()
+                    .enumerate()
+                    .map(|(channel, c)| {
+                        let alt = &c["alternatives"][0];
+                        ChannelTranscript {
+                            channel,
+                            text: alt["transcript"].as_str().unwrap_or("").to_string(),
+                            words: Word::parse_list(&alt["words"]),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl ChannelTranscript {
+    /// Channels of a streaming result: live multichannel sends one message per
+    /// channel (tagged with `channel_index`), other responses carry `results.channels[*]`
+    pub fn from_live(json: &Value) -> Vec<ChannelTranscript> {
+        if json["results"]["channels"].is_array() {
+            return ChannelTranscript::parse_all(json);
+        }
+        let alt = &json["channel"]["alternatives"][0];
+        if alt.is_null() {
+            return Vec::new();
+        }
+        vec![ChannelTranscript {
+            channel: json["channel_index"][0].as_u64().unwrap_or(0) as usize,
+            text: alt["transcript"].as_str().unwrap_or("").to_string(),
+            words: Word::parse_list(&alt["words"]),
+        }]
+    }
+}
+
+/// 🌐 Language Deepgram detected (`detect_language=true`), emitted as `detected_language`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// BCP-47 code, e.g. "es"
+    pub language: String,
+    /// 0..1, when Deepgram reported it
+    pub confidence: Option<f64>,
+}
+
+impl DetectedLanguage {
+    /// From a prerecorded response's first channel; `None` if Deepgram left the field out
+    pub fn from_response(json: &Value) -> Option<DetectedLanguage> {
+        let channel = &json["results"]["channels"][0];
+        let language = channel["detected_language"].as_str().filter(|l| !l.trim().is_empty())?;
+        Some(DetectedLanguage {
+            language: language.to_string(),
+            confidence: channel["language_confidence"].as_f64(),
+        })
+    }
+}
+
+/// 📂 Result of transcribing a file, emitted as `file_transcribe_done`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileTranscript {
+    pub file_name: String,
+    pub text: String,
+    /// Audio length in seconds, when Deepgram reported it
+    pub duration: Option<f64>,
+    /// One segment per Deepgram utterance (`utterances=true`)
+    pub segments: Vec<Segment>,
+    pub words: Vec<Word>,
+    /// Deepgram paragraphs (`paragraphs=true`); `text` then holds them separated by blank lines
+    pub paragraphs: Vec<Paragraph>,
+    /// Deepgram's summary (`summarize=v2`)
+    pub summary: Option<String>,
+    /// Spoken language, when detection was requested and Deepgram reported one
+    pub detected_language: Option<DetectedLanguage>,
+}
+
+impl FileTranscript {
+    /// Parse a prerecorded response requested with `utterances=true` (`file_name` left empty);
+    /// with paragraph data the text keeps Deepgram's paragraph breaks
+    pub fn from_response(json: &Value) -> FileTranscript {
+        let alt = top_alternative(json);
+        let segments = json["results"]["utterances"]
+            .as_array()
+            .map(|utterances| {
+                utterances
+                    .iter()
+                    .filter_map(|u| {
+                        Some(Segment {
+                            start: u["start"].as_f64()?,
+                            end: u["end"].as_f64()?,
+                            text: u["transcript"].as_str()?.to_string(),
+                            speaker: u["speaker"].as_u64().map(|s| s as u32),
+                            channel: u["channel"].as_u64().map(|c| c as usize),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let paragraphs = Paragraph::parse_list(alt);
+        let text = if paragraphs.is_empty() {
+            alt["transcript"].as_str().unwrap_or("").to_string()
+        } else {
+            Paragraph::join(&paragraphs)
+        };
+        FileTranscript {
+            file_name: String::new(),
+            text,
+            duration: json["metadata"]["duration"].as_f64(),
+            segments,
+            words: Word::parse_list(&alt["words"]),
+            paragraphs,
+            summary: json["results"]["summary"]["short"].as_str().map(str::to_string).filter(|s| !s.trim().is_empty()),
+            detected_language: DetectedLanguage::from_response(json),
+        }
+    }
+
+    /// Append the transcript of a later piece of the same file that starts `offset` seconds in
+    pub fn append(&mut self, next: FileTranscript, offset: f64) {
+        if !next.text.trim().is_empty() {
+            if !self.text.is_empty() {
+                // Keep paragraph breaks between pieces that have them
+                self.text.push_str(if next.paragraphs.is_empty() { " " } else { "\n\n" });
+            }
+            self.text.push_str(&next.text);
+        }
+        self.duration = match (self.duration, next.duration) {
+            (_, Some(d)) => Some(offset + d),
+            (d, None) => d,
+        };
+        self.segments.extend(next.segments.into_iter().map(|s| Segment { start: s.start + offset, end: s.end + offset, ..s }));
+        self.words.extend(next.words.into_iter().map(|w| Word { start: w.start + offset, end: w.end + offset, ..w }));
+        // The first piece's language stands for the file
+        self.detected_language = self.detected_language.take().or(next.detected_language);
+        self.summary = match (self.summary.take(), next.summary) {
+            (Some(a), Some(b)) => Some(format!("{}\n\n{}", a, b)),
+            (a, b) => a.or(b),
+        };
+        self.paragraphs.extend(next.paragraphs.into_iter().map(|p| Paragraph { start: p.start + offset, end: p.end + offset, ..p }));
+    }
+}
+
+/// Channel a streaming result belongs to, when the stream has more than one
+pub fn live_channel(json: &Value) -> Option<usize> {
+    let index = json["channel_index"].as_array()?;
+    let total = index.get(1)?.as_u64()?;
+    (total > 1).then(|| index[0].as_u64()).flatten().map(|c| c as usize)
+}
+
+/// Merge per-channel words into one timestamp-ordered conversation, one segment
+/// per run of consecutive words from the same channel.
+pub fn interleave_channels(channels: &[ChannelTranscript]) -> Vec<Segment> {
+    let mut words: Vec<(usize, &Word)> = channels
+        .iter()
+        .flat_map(|c| c.words.iter().map(move |w| (c.channel, w)))
+        .collect();
+    words.sort_by(|a, b| a.1.start.total_cmp(&b.1.start));
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for (channel, w) in words {
+        match segments.last_mut() {
+            Some(seg) if seg.channel == Some(channel) => {
+                seg.end = seg.end.max(w.end);
+                seg.text.push(' ');
+                seg.text.push_str(&w.word);
+            }
+            _ => segments.push(Segment {
+                start: w.start,
+                end: w.end,
+                text: w.word.clone(),
+                speaker: None,
+                channel: Some(channel),
+            }),
+        }
+    }
+    segments
+}
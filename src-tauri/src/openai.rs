@@ -0,0 +1,143 @@
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+use tauri::AppHandle;
+use tokio::sync::mpsc::Receiver;
+
+use crate::backend::TranscriptionBackend;
+use crate::model::{FileTranscript, Segment};
+use crate::options::{FileOptions, RecordingOptions};
+
+const TRANSCRIPTIONS_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+const MODEL: &str = "whisper-1";
+/// OpenAI rejects uploads above this size
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+pub const MISSING_API_KEY: &str = "OpenAI API key not configured";
+
+/// 🔑 The OpenAI API key (config first, then `OPENAI_API_KEY`)
+pub fn api_key() -> Result<String, String> {
+    crate::config::load()
+        .openai_api_key
+        .map(|k| k.0)
+        .filter(|k| !k.trim().is_empty())
+        .or_else(|| std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.trim().is_empty()))
+        .ok_or_else(|| MISSING_API_KEY.to_string())
+}
+
+/// 🤖 OpenAI's hosted Whisper as a file-only [`TranscriptionBackend`]
+pub struct OpenAiBackend {
+    api_key: String,
+}
+
+impl OpenAiBackend {
+    /// Fails when no API key is configured
+    pub fn new() -> Result<Self, String> {
+        Ok(OpenAiBackend { api_key: api_key()? })
+    }
+}
+
+/// File name for the upload part; OpenAI picks the decoder from its extension
+fn upload_name(mime: Option<&str>) -> &'static str {
+    match mime {
+        Some("audio/wav") => "audio.wav",
+        Some("audio/mp4") => "audio.m4a",
+        Some("audio/flac") => "audio.flac",
+        Some("audio/ogg") => "audio.ogg",
+        Some("audio/webm") => "audio.webm",
+        _ => "audio.mp3",
+    }
+}
+
+/// Parse a `verbose_json` response
+fn parse_response(json: &Value) -> FileTranscript {
+    let segments = json["segments"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|s| {
+                    Some(Segment {
+                        start: s["start"].as_f64()?,
+                        end: s["end"].as_f64()?,
+                        text: s["text"].as_str()?.trim().to_string(),
+                        speaker: None,
+                        channel: None,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    FileTranscript {
+        file_name: String::new(),
+        text: json["text"].as_str().unwrap_or("").trim().to_string(),
+        duration: json["duration"].as_f64(),
+        segments,
+        words: Vec::new(),
+        paragraphs: Vec::new(),
+        summary: None,
+        detected_language: None,
+    }
+}
+
+impl TranscriptionBackend for OpenAiBackend {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn stream(
+        &self,
+        _rx: Receiver<Vec<i16>>,
+        _app: AppHandle,
+        _sample_rate: u32,
+        _channels: u16,
+        _options: RecordingOptions,
+    ) -> BoxFuture<'static, ()> {
+        // `start_recording` refuses file-only backends before capture starts
+        Box::pin(async { eprintln!("❌ OpenAI transcription is file-only") })
+    }
+
+    fn transcribe_file(
+        &self,
+        audio: Vec<u8>,
+        mime: Option<&'static str>,
+        _options: &FileOptions,
+    ) -> BoxFuture<'static, Result<FileTranscript, String>> {
+        let api_key = self.api_key.clone();
+        Box::pin(async move {
+            if audio.len() > MAX_UPLOAD_BYTES {
+                return Err(format!(
+                    "File is too large for OpenAI ({:.1} MB; the limit is 25 MB). Try a compressed format or a shorter clip.",
+                    audio.len() as f64 / (1024.0 * 1024.0)
+                ));
+            }
+
+            let mut file = reqwest::multipart::Part::bytes(audio).file_name(upload_name(mime));
+            if let Some(mime) = mime {
+                file = file.mime_str(mime).map_err(|e| e.to_string())?;
+            }
+            let form = reqwest::multipart::Form::new()
+                .part("file", file)
+                .text("model", MODEL)
+                .text("response_format", "verbose_json");
+
+            let response = reqwest::Client::new()
+                .post(TRANSCRIPTIONS_URL)
+                .bearer_auth(api_key)
+                .multipart(form)
+                .send()
+                .await
+                .map_err(|e| format!("HTTP error: {}", e))?;
+            let json: Value = response.json().await.map_err(|e| format!("JSON parse error: {}", e))?;
+            println!("📨 OpenAI JSON: {}", json);
+
+            if let Some(message) = json["error"]["message"].as_str() {
+                return Err(format!("OpenAI error: {}", message));
+            }
+            Ok(parse_response(&json))
+        })
+    }
+}
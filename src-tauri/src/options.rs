@@ -0,0 +1,255 @@
+use serde::Deserialize;
+
+/// Sample rates accepted for `send_rate` (raw linear16 streaming)
+pub const SUPPORTED_SEND_RATES: &[u32] = &[8000, 16000, 22050, 24000, 32000, 44100, 48000];
+
+/// Deepgram rejects requests with too many keyword params.
+const MAX_CONTEXT_KEYWORDS: usize = 25;
+/// Upper bound on `keywords` + `keyword_boosts` per request (Deepgram's limit)
+const MAX_KEYWORDS: usize = 100;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "about", "this", "that", "from", "are", "was", "were", "will",
+    "into", "our", "your", "their", "has", "have", "had", "not", "but", "who", "what", "when",
+    "where", "which", "how", "its", "also", "between", "some", "any", "all", "can",
+];
+
+/// Deepgram's lower bound for `utterance_end_ms`
+const MIN_UTTERANCE_END_MS: u32 = 1000;
+
+/// Largest fixed `gain` accepted
+const MAX_GAIN: f32 = 20.0;
+
+/// Categories accepted by `redact`
+pub const REDACT_CATEGORIES: &[&str] = &["pii", "ssn", "pci", "numbers"];
+
+/// Model used when the frontend doesn't pick one
+pub const DEFAULT_MODEL: &str = "nova-2";
+/// Language used when the frontend doesn't pick one
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// 🎛️ Per-recording options passed by the frontend to `start_recording`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RecordingOptions {
+    /// Transcription backend, see [`crate::backend::BACKENDS`] (default Deepgram)
+    pub backend: Option<String>,
+    /// Deepgram model (default [`DEFAULT_MODEL`])
+    pub model: Option<String>,
+    /// BCP-47 language code, e.g. "es" or "de" (default [`DEFAULT_LANGUAGE`])
+    pub language: Option<String>,
+    pub punctuate: bool,
+    /// Deepgram `smart_format`: punctuation, capitalization and formatted
+    /// numbers/dates/currency (off by default: it can delay live results slightly)
+    pub smart_format: bool,
+    /// Also stream non-final hypotheses (`interim_transcript` events)
+    pub interim_results: bool,
+    /// Label words with speakers (`diarized_transcript` events)
+    pub diarize: bool,
+    /// Capture two channels (e.g. one mic per speaker) and transcribe each separately
+    /// (`channel_transcript` events). Uses in-process capture; mono devices stay mono.
+    pub multichannel: bool,
+    /// Finalize an utterance after this many ms of silence (unset: Deepgram's default)
+    pub endpointing: Option<u32>,
+    /// Emit `utterance_end` after this gap between words (≥ 1000 ms, needs `interim_results`)
+    pub utterance_end_ms: Option<u32>,
+    /// Freeform "what is this audio about" hint (names, jargon, topic).
+    ///
+    /// - Deepgram: decomposed into `keywords=` boosts, see [`RecordingOptions::context_keywords`]
+    /// - Prompt-based backends (e.g. Whisper): passed through verbatim as the prompt
+    pub context: Option<String>,
+    /// Mask profanity in the transcript (`profanity_filter=true`)
+    pub profanity_filter: bool,
+    /// Redaction categories, one `redact=` each: "pii", "ssn", "pci" or "numbers".
+    /// Redacted words come back as `[REDACTED]`-style tokens and are kept verbatim.
+    pub redact: Vec<String>,
+    /// Terms to boost with Deepgram's default intensity (product names, jargon…)
+    pub keywords: Vec<String>,
+    /// Terms with an explicit boost (`keywords=term:intensity`); negative values suppress
+    pub keyword_boosts: Vec<(String, f32)>,
+    /// Input gain applied to captured audio before metering and sending (1.0 = unchanged)
+    pub gain: f32,
+    /// Continuously adjust gain toward a comfortable speech level (on top of `gain`)
+    pub auto_gain: bool,
+    /// Noise gate: audio quieter than this RMS (0..1 of full scale) isn't sent to
+    /// Deepgram, apart from a short tail after speech. 0 = send everything.
+    pub silence_threshold: f32,
+    /// Sample rate audio is sent to Deepgram at. Unset: 16 kHz, resampling if the
+    /// device differs. Set it to the device's native rate to skip resampling.
+    pub send_rate: Option<u32>,
+    /// Raw query parameters appended to Deepgram requests, for features without a
+    /// typed option yet. Parameters the app sets itself are skipped with a warning.
+    pub extra_params: Vec<(String, String)>,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        RecordingOptions {
+            backend: None,
+            model: None,
+            language: None,
+            punctuate: true,
+            smart_format: false,
+            interim_results: false,
+            diarize: false,
+            multichannel: false,
+            endpointing: None,
+            utterance_end_ms: None,
+            context: None,
+            profanity_filter: false,
+            redact: Vec::new(),
+            keywords: Vec::new(),
+            keyword_boosts: Vec::new(),
+            gain: 1.0,
+            auto_gain: false,
+            silence_threshold: 0.0,
+            send_rate: None,
+            extra_params: Vec::new(),
+        }
+    }
+}
+
+/// 📂 Options for transcribing a file (`transcribe_file_cmd`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FileOptions {
+    /// Deepgram `smart_format` (numbers, dates, currency…) plus paragraph breaks.
+    /// On by default: latency doesn't matter for files.
+    pub smart_format: bool,
+    /// Deepgram `summarize=v2`: a short summary, emitted as `summary` (English audio)
+    pub summarize: bool,
+    /// Deepgram `detect_language=true`: transcribe in the spoken language and report
+    /// it as `detected_language`
+    pub detect_language: bool,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        FileOptions { smart_format: true, summarize: true, detect_language: false }
+    }
+}
+
+impl RecordingOptions {
+    pub fn model(&self) -> &str {
+        self.model.as_deref().filter(|m| !m.trim().is_empty()).unwrap_or(DEFAULT_MODEL)
+    }
+
+    pub fn language(&self) -> &str {
+        self.language.as_deref().filter(|l| !l.trim().is_empty()).unwrap_or(DEFAULT_LANGUAGE)
+    }
+
+    /// Reject option values Deepgram would refuse
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.gain.is_finite() && self.gain > 0.0 && self.gain <= MAX_GAIN) {
+            return Err(format!("Gain must be greater than 0 and at most {} (got {})", MAX_GAIN, self.gain));
+        }
+        if !(self.silence_threshold.is_finite() && (0.0..1.0).contains(&self.silence_threshold)) {
+            return Err(format!("silence_threshold must be at least 0 and below 1 (got {})", self.silence_threshold));
+        }
+        if let Some(rate) = self.send_rate {
+            if !SUPPORTED_SEND_RATES.contains(&rate) {
+                return Err(format!("Unsupported send rate {} Hz (supported: {:?})", rate, SUPPORTED_SEND_RATES));
+            }
+        }
+        if let Some(ms) = self.utterance_end_ms {
+            if ms < MIN_UTTERANCE_END_MS {
+                return Err(format!("utterance_end_ms must be at least {} (got {})", MIN_UTTERANCE_END_MS, ms));
+            }
+            if !self.interim_results {
+                return Err("utterance_end_ms requires interim_results".into());
+            }
+        }
+        if let Some(bad) = self.redact.iter().find(|c| !REDACT_CATEGORIES.contains(&c.as_str())) {
+            return Err(format!("Unknown redaction category {:?} (supported: {:?})", bad, REDACT_CATEGORIES));
+        }
+        self.validate_keywords()?;
+        validate_extra_params(&self.extra_params)
+    }
+
+    fn validate_keywords(&self) -> Result<(), String> {
+        let count = self.keywords.len() + self.keyword_boosts.len();
+        if count > MAX_KEYWORDS {
+            return Err(format!("Too many keywords: {} (Deepgram allows at most {})", count, MAX_KEYWORDS));
+        }
+        let terms = self.keywords.iter().chain(self.keyword_boosts.iter().map(|(t, _)| t));
+        for term in terms {
+            // `:` separates the term from its intensity
+            if term.trim().is_empty() || term.contains(':') {
+                return Err(format!("Invalid keyword {:?}: must be non-empty and must not contain ':'", term));
+            }
+        }
+        if let Some((term, boost)) = self.keyword_boosts.iter().find(|(_, b)| !b.is_finite()) {
+            return Err(format!("Invalid boost {} for keyword {:?}", boost, term));
+        }
+        Ok(())
+    }
+
+    /// `keywords=` values for Deepgram: explicit keywords and boosts first, then
+    /// terms from `context` while there is room (case-insensitive duplicates dropped).
+    /// Values are not URL-encoded yet.
+    pub fn keyword_params(&self) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        let mut out: Vec<String> = Vec::new();
+        let mut add = |term: &str, value: String| {
+            let term = term.trim();
+            if out.len() < MAX_KEYWORDS && !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+                terms.push(term.to_string());
+                out.push(value);
+            }
+        };
+        for (term, boost) in &self.keyword_boosts {
+            add(term, format!("{}:{}", term.trim(), boost));
+        }
+        for term in &self.keywords {
+            add(term, term.trim().to_string());
+        }
+        for term in self.context_keywords() {
+            add(&term, term.clone());
+        }
+        out
+    }
+
+    /// Distinct, non-trivial terms from `context`, in order of appearance.
+    pub fn context_keywords(&self) -> Vec<String> {
+        let Some(context) = self.context.as_deref() else { return Vec::new() };
+        let mut out: Vec<String> = Vec::new();
+        for raw in context.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '-')) {
+            let term = raw.trim_matches(|c: char| c == '\'' || c == '-');
+            if term.chars().count() < 3 || STOPWORDS.contains(&term.to_lowercase().as_str()) {
+                continue;
+            }
+            if out.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+                continue;
+            }
+            out.push(term.to_string());
+            if out.len() == MAX_CONTEXT_KEYWORDS {
+                break;
+            }
+        }
+        out
+    }
+}
+
+/// Extra parameter keys must be plain query names (values are URL-encoded)
+pub fn validate_extra_params(params: &[(String, String)]) -> Result<(), String> {
+    for (key, _) in params {
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+            return Err(format!("Invalid extra parameter name: {:?}", key));
+        }
+    }
+    Ok(())
+}
+
+/// 🔗 Append `extra` to a query string, skipping keys in `managed` (with a warning)
+pub fn append_extra_params(url: &mut String, extra: &[(String, String)], managed: &[&str]) {
+    for (key, value) in extra {
+        if managed.contains(&key.as_str()) {
+            eprintln!("⚠️ Ignoring extra parameter {:?}: it is set by the app", key);
+            continue;
+        }
+        url.push('&');
+        url.push_str(key);
+        url.push('=');
+        url.extend(url::form_urlencoded::byte_serialize(value.as_bytes()));
+    }
+}
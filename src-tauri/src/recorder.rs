@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::wav::WavStreamWriter;
+
+/// 💾 WAV copy of the captured (device-rate) audio for the current session
+struct Recorder {
+    writer: WavStreamWriter,
+    path: PathBuf,
+    sample_rate: u32,
+    channels: u16,
+    /// Samples written, all channels
+    samples: u64,
+    app: AppHandle,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Result of a finished WAV recording (`recording_saved` event)
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedRecording {
+    pub path: String,
+    pub duration_secs: f64,
+}
+
+/// 💾 Start writing captured (interleaved) audio to `path`; replaces any recording still open
+pub fn start(path: &Path, sample_rate: u32, channels: u16, app: AppHandle) -> Result<(), String> {
+    let writer = WavStreamWriter::create(path, sample_rate, channels)?;
+    println!("💾 Recording audio to {:?} ({} Hz, {} ch)", path, sample_rate, channels);
+    let previous = RECORDER.lock().unwrap().replace(Recorder {
+        writer,
+        path: path.to_path_buf(),
+        sample_rate,
+        channels,
+        samples: 0,
+        app,
+    });
+    if let Some(old) = previous {
+        let _ = old.writer.finalize();
+    }
+    Ok(())
+}
+
+/// Append captured samples; a write error (e.g. disk full) closes the file but
+/// leaves transcription running
+pub fn write(samples: &[i16]) {
+    let mut guard = RECORDER.lock().unwrap();
+    let Some(rec) = guard.as_mut() else { return };
+    match rec.writer.write(samples) {
+        Ok(()) => rec.samples += samples.len() as u64,
+        Err(e) => {
+            eprintln!("❌ Stopped writing {:?}: {}", rec.path, e);
+            let rec = guard.take().unwrap();
+            let _ = rec.app.emit("recording_file_error", serde_json::json!({
+                "path": rec.path.to_string_lossy(),
+                "error": e,
+            }));
+            // Keep whatever made it to disk playable
+            let _ = rec.writer.finalize();
+        }
+    }
+}
+
+/// 💾 Finalize the WAV header; `None` when nothing was being recorded
+pub fn finish() -> Option<Result<SavedRecording, String>> {
+    let rec = RECORDER.lock().unwrap().take()?;
+    let path = rec.path.to_string_lossy().into_owned();
+    let duration_secs = rec.samples as f64 / (rec.sample_rate as f64 * rec.channels as f64);
+    match rec.writer.finalize() {
+        Ok(()) => {
+            println!("💾 Saved recording {} ({:.1}s)", path, duration_secs);
+            let saved = SavedRecording { path, duration_secs };
+            let _ = rec.app.emit("recording_saved", &saved);
+            Some(Ok(saved))
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            let _ = rec.app.emit("recording_file_error", serde_json::json!({ "path": path, "error": e }));
+            Some(Err(e))
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// 🚦 Lifecycle of the (single) recording session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    Idle,
+    Recording,
+    /// Mic and Deepgram connection stay open, but captured audio is dropped
+    Paused,
+}
+
+impl SessionState {
+    /// Recording or paused: a session is open
+    pub fn is_active(self) -> bool {
+        matches!(self, SessionState::Recording | SessionState::Paused)
+    }
+}
+
+/// How captured audio reaches the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    /// The `audio_worker` helper process
+    Worker,
+    /// In-process CPAL stream
+    Fallback,
+}
+
+/// 🎙️ What `start_recording` actually started
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub device: String,
+    pub mode: CaptureMode,
+    /// Capture sample rate (before any resampling for Deepgram)
+    pub sample_rate: u32,
+    /// 2 for stereo (`multichannel`) capture, otherwise 1
+    pub channels: u16,
+}
+
+static STATE: Mutex<SessionState> = Mutex::new(SessionState::Idle);
+/// When the current (or last) recording started
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+/// Mirrors `state() == Paused` without a lock, for the audio callbacks
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn state() -> SessionState {
+    *STATE.lock().unwrap()
+}
+
+pub fn set_state(state: SessionState) {
+    *STATE.lock().unwrap() = state;
+    PAUSED.store(state == SessionState::Paused, Ordering::Relaxed);
+}
+
+/// Whether captured frames should be dropped right now
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// The pause flag itself, for code that shouldn't depend on this module
+pub fn paused_flag() -> &'static AtomicBool {
+    &PAUSED
+}
+
+/// ⏱️ Start the session clock (called by `start_recording`)
+pub fn mark_started() {
+    *STARTED.lock().unwrap() = Some(Instant::now());
+}
+
+/// ⏱️ Wall-clock seconds since the recording started (pauses included)
+pub fn elapsed_secs() -> Option<f64> {
+    STARTED.lock().unwrap().map(|t| t.elapsed().as_secs_f64())
+}
@@ -0,0 +1,22 @@
+use serde_json::json;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 🧾 `HEARD_IT_STDOUT_JSON=1`: finalized transcripts go to stdout as JSON lines
+/// (`{"ts": <unix ms>, "text": ...}`) for piping into other tools; logs move to stderr.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| std::env::var("HEARD_IT_STDOUT_JSON").as_deref() == Ok("1"))
+}
+
+/// Write one finalized transcript line (no-op unless enabled)
+pub fn write_transcript(text: &str) {
+    if !enabled() {
+        return;
+    }
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{}", json!({ "ts": ts, "text": text }));
+    let _ = out.flush();
+}
@@ -0,0 +1,275 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::stdout_json;
+use crate::model::{FileTranscript, Segment, TranscriptEvent, Word};
+use crate::vad::SpeechSpan;
+
+/// 🗂️ Finalized transcript data accumulated for the current session
+pub struct TranscriptStore {
+    segments: Vec<Segment>,
+    words: Vec<Word>,
+    /// VAD speech boundaries, for caption timing when word timings are missing
+    speech: Vec<SpeechSpan>,
+}
+
+impl TranscriptStore {
+    const fn new() -> Self {
+        TranscriptStore { segments: Vec::new(), words: Vec::new(), speech: Vec::new() }
+    }
+}
+
+static STORE: Mutex<TranscriptStore> = Mutex::new(TranscriptStore::new());
+
+/// Append a finalized segment
+pub fn append_segment(segment: Segment) {
+    STORE.lock().unwrap().segments.push(segment);
+}
+
+/// Append finalized words from a Deepgram result
+pub fn append_words(words: &[Word]) {
+    STORE.lock().unwrap().words.extend_from_slice(words);
+}
+
+/// Record a detected speech span
+pub fn append_speech(span: SpeechSpan) {
+    STORE.lock().unwrap().speech.push(span);
+}
+
+/// Snapshot of the VAD speech spans so far
+pub fn speech_spans() -> Vec<SpeechSpan> {
+    STORE.lock().unwrap().speech.clone()
+}
+
+/// Snapshot of all finalized segments so far
+pub fn segments() -> Vec<Segment> {
+    STORE.lock().unwrap().segments.clone()
+}
+
+/// Snapshot of all finalized words so far
+pub fn words() -> Vec<Word> {
+    STORE.lock().unwrap().words.clone()
+}
+
+/// Finalized segments joined into plain text
+pub fn full_text() -> String {
+    let store = STORE.lock().unwrap();
+    store.segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join(" ")
+}
+
+/// 📜 The whole session transcript, as returned by `get_transcript`
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub text: String,
+    pub segments: Vec<Segment>,
+    pub words: Vec<Word>,
+}
+
+/// Snapshot of the text, segments and words so far
+pub fn snapshot() -> Snapshot {
+    Snapshot { text: full_text(), segments: segments(), words: words() }
+}
+
+/// 📂 Replace the store with a transcribed file (a single segment when the backend gave none)
+pub fn load_file(result: &FileTranscript) {
+    let mut store = STORE.lock().unwrap();
+    store.segments = if result.segments.is_empty() && !result.text.trim().is_empty() {
+        vec![Segment {
+            start: 0.0,
+            end: result.duration.unwrap_or(0.0),
+            text: result.text.clone(),
+            speaker: None,
+            channel: None,
+        }]
+    } else {
+        result.segments.clone()
+    };
+    store.words = result.words.clone();
+    store.speech.clear();
+}
+
+/// Whether a word and a segment agree on a speaker/channel tag (unset matches anything)
+fn same_tag<T: PartialEq>(a: Option<T>, b: Option<T>) -> bool {
+    a.is_none() || b.is_none() || a == b
+}
+
+/// Whether `word` is part of `seg`: from its speaker and channel, starting in `[start, end)`
+fn belongs_to(word: &Word, seg: &Segment) -> bool {
+    word.start >= seg.start
+        && word.start < seg.end
+        && same_tag(word.speaker, seg.speaker)
+        && same_tag(word.channel, seg.channel)
+}
+
+impl TranscriptStore {
+    /// Rebuild the text of the segment `word` belongs to from its (edited) words
+    fn resync_segment_of(&mut self, word: &Word) {
+        let Some(seg) = self.segments.iter_mut().find(|s| belongs_to(word, s)) else { return };
+        seg.text = self
+            .words
+            .iter()
+            .filter(|w| belongs_to(w, seg))
+            .map(|w| w.word.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    fn update_word(&mut self, index: usize, text: String) -> Result<(), String> {
+        let len = self.words.len();
+        let word = self.words.get_mut(index).ok_or_else(|| format!("Word index {} out of range ({} words)", index, len))?;
+        word.word = text;
+        let word = word.clone();
+        self.resync_segment_of(&word);
+        Ok(())
+    }
+
+    fn delete_word(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.words.len() {
+            return Err(format!("Word index {} out of range ({} words)", index, self.words.len()));
+        }
+        let removed = self.words.remove(index);
+        self.resync_segment_of(&removed);
+        Ok(())
+    }
+
+    fn insert_word(&mut self, index: usize, word: Word) -> Result<(), String> {
+        if index > self.words.len() {
+            return Err(format!("Word index {} out of range ({} words)", index, self.words.len()));
+        }
+        self.words.insert(index, word.clone());
+        self.resync_segment_of(&word);
+        Ok(())
+    }
+}
+
+/// ✏️ Replace the text of word `index`
+pub fn update_word(index: usize, text: String) -> Result<(), String> {
+    STORE.lock().unwrap().update_word(index, text)
+}
+
+/// ✏️ Remove word `index`
+pub fn delete_word(index: usize) -> Result<(), String> {
+    STORE.lock().unwrap().delete_word(index)
+}
+
+/// ✏️ Insert `word` before position `index` (`index == len` appends)
+pub fn insert_word(index: usize, word: Word) -> Result<(), String> {
+    STORE.lock().unwrap().insert_word(index, word)
+}
+
+/// Drop everything (called when a new recording or file transcription starts)
+pub fn clear() {
+    let mut store = STORE.lock().unwrap();
+    store.segments.clear();
+    store.words.clear();
+    store.speech.clear();
+}
+
+/// 📝 Emit a final result as `transcript` and an interim one as `interim_transcript`;
+/// the payload is a bare string when `legacy` (the `legacy_transcript_event` setting,
+/// read once per stream by the caller) is set
+pub fn emit(app: &AppHandle, event: TranscriptEvent, legacy: bool) {
+    let name = if event.is_final {
+        println!("📝 TRANSCRIPT: {}", event.text);
+        stdout_json::write_transcript(&event.text);
+        "transcript"
+    } else {
+        "interim_transcript"
+    };
+    if legacy {
+        let _ = app.emit(name, event.text);
+    } else {
+        let _ = app.emit(name, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start: f64, speaker: Option<u32>, channel: Option<usize>) -> Word {
+        Word { word: text.into(), start, end: start + 0.4, confidence: 0.9, speaker, channel }
+    }
+
+    fn segment(start: f64, end: f64, text: &str, speaker: Option<u32>, channel: Option<usize>) -> Segment {
+        Segment { start, end, text: text.into(), speaker, channel }
+    }
+
+    fn texts(store: &TranscriptStore) -> Vec<&str> {
+        store.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn edits_at_a_shared_boundary_go_to_the_later_segment() {
+        let mut store = TranscriptStore::new();
+        store.segments = vec![segment(0.0, 1.0, "hello world", None, None), segment(1.0, 2.0, "next one", None, None)];
+        store.words = vec![
+            word("hello", 0.0, None, None),
+            word("world", 0.5, None, None),
+            word("next", 1.0, None, None),
+            word("one", 1.5, None, None),
+        ];
+
+        store.update_word(2, "Next".into()).unwrap();
+        assert_eq!(texts(&store), ["hello world", "Next one"]);
+
+        store.delete_word(2).unwrap();
+        assert_eq!(texts(&store), ["hello world", "one"]);
+
+        store.insert_word(2, word("then", 1.0, None, None)).unwrap();
+        assert_eq!(texts(&store), ["hello world", "then one"]);
+    }
+
+    #[test]
+    fn edits_in_overlapping_turns_stay_with_their_speaker() {
+        let mut store = TranscriptStore::new();
+        store.segments = vec![segment(0.0, 2.0, "so then", Some(0), None), segment(1.0, 3.0, "right yes", Some(1), None)];
+        store.words = vec![
+            word("so", 0.0, Some(0), None),
+            word("right", 1.0, Some(1), None),
+            word("then", 1.5, Some(0), None),
+            word("yes", 2.0, Some(1), None),
+        ];
+
+        store.update_word(1, "Right".into()).unwrap();
+        assert_eq!(texts(&store), ["so then", "Right yes"]);
+
+        store.delete_word(2).unwrap();
+        assert_eq!(texts(&store), ["so", "Right yes"]);
+
+        store.insert_word(2, word("oh", 1.2, Some(1), None)).unwrap();
+        assert_eq!(texts(&store), ["so", "Right oh yes"]);
+    }
+
+    #[test]
+    fn edits_in_overlapping_channels_stay_in_their_channel() {
+        let mut store = TranscriptStore::new();
+        store.segments = vec![segment(0.0, 1.0, "left side", None, Some(0)), segment(0.0, 1.0, "right side", None, Some(1))];
+        store.words = vec![
+            word("left", 0.0, None, Some(0)),
+            word("right", 0.0, None, Some(1)),
+            word("side", 0.5, None, Some(0)),
+            word("side", 0.5, None, Some(1)),
+        ];
+
+        store.update_word(3, "channel".into()).unwrap();
+        assert_eq!(texts(&store), ["left side", "right channel"]);
+
+        store.delete_word(0).unwrap();
+        assert_eq!(texts(&store), ["side", "right channel"]);
+
+        store.insert_word(0, word("far", 0.0, None, Some(0))).unwrap();
+        assert_eq!(texts(&store), ["far side", "right channel"]);
+    }
+
+    #[test]
+    fn out_of_range_edits_are_rejected() {
+        let mut store = TranscriptStore::new();
+        store.words = vec![word("only", 0.0, None, None)];
+        assert!(store.update_word(1, "x".into()).is_err());
+        assert!(store.delete_word(1).is_err());
+        assert!(store.insert_word(2, word("x", 0.0, None, None)).is_err());
+        assert_eq!(store.words.len(), 1);
+    }
+}
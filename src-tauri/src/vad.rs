@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+/// Analysis window length.
+const FRAME_MS: u32 = 20;
+/// Normalized RMS above which a frame counts as speech.
+const SPEECH_RMS: f32 = 0.02;
+/// Silence shorter than this doesn't end a span (keeps words in one span).
+const HANGOVER_SECS: f64 = 0.3;
+/// Spans shorter than this are treated as clicks/noise.
+const MIN_SPEECH_SECS: f64 = 0.2;
+/// Noise gate tail: audio keeps flowing this long after the last loud frame, so
+/// short dips between words (and trailing consonants) aren't cut.
+const GATE_HOLD_SECS: f64 = 0.6;
+
+/// 🗣️ A stretch of detected speech (seconds from session start)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpeechSpan {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// 🗣️ Energy-based voice activity detector over the mono audio sent for transcription
+pub struct VoiceActivity {
+    sample_rate: u32,
+    frame_len: usize,
+    pending: Vec<i16>,
+    /// Samples consumed so far (whole frames only)
+    position: u64,
+    /// Start of the current span and the end of its last speech frame
+    open: Option<(f64, f64)>,
+}
+
+impl VoiceActivity {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let frame_len = (sample_rate * FRAME_MS / 1000).max(1) as usize;
+        VoiceActivity { sample_rate, frame_len, pending: Vec::new(), position: 0, open: None }
+    }
+
+    /// Feed samples; returns spans that closed within them
+    pub fn push(&mut self, samples: &[i16]) -> Vec<SpeechSpan> {
+        self.pending.extend_from_slice(samples);
+        let mut closed = Vec::new();
+        let mut consumed = 0;
+
+        while self.pending.len() - consumed >= self.frame_len {
+            let frame = &self.pending[consumed..consumed + self.frame_len];
+            consumed += self.frame_len;
+
+            let rms = frame_rms(frame);
+
+            let frame_start = self.position as f64 / self.sample_rate as f64;
+            self.position += self.frame_len as u64;
+            let frame_end = self.position as f64 / self.sample_rate as f64;
+
+            if rms >= SPEECH_RMS {
+                self.open = Some(match self.open {
+                    Some((start, _)) => (start, frame_end),
+                    None => (frame_start, frame_end),
+                });
+            } else if let Some((start, last_speech)) = self.open {
+                if frame_end - last_speech >= HANGOVER_SECS {
+                    self.open = None;
+                    if last_speech - start >= MIN_SPEECH_SECS {
+                        closed.push(SpeechSpan { start, end: last_speech });
+                    }
+                }
+            }
+        }
+
+        self.pending.drain(..consumed);
+        closed
+    }
+
+    /// Close any span still open at the end of the stream
+    pub fn finish(&mut self) -> Option<SpeechSpan> {
+        let (start, end) = self.open.take()?;
+        (end - start >= MIN_SPEECH_SECS).then_some(SpeechSpan { start, end })
+    }
+}
+
+/// Normalized RMS (0..1 of full scale) of a frame
+fn frame_rms(frame: &[i16]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f64 = frame.iter().map(|s| (*s as f64) * (*s as f64)).sum();
+    ((sum_sq / frame.len() as f64).sqrt() / i16::MAX as f64) as f32
+}
+
+/// 🚪 Noise gate over mono audio: decides which batches are worth sending
+pub struct NoiseGate {
+    threshold: f32,
+    frame_len: usize,
+    hold_samples: u64,
+    /// Samples still let through after the last loud frame
+    hold_left: u64,
+}
+
+impl NoiseGate {
+    pub fn new(threshold: f32, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        NoiseGate {
+            threshold,
+            frame_len: (sample_rate * FRAME_MS / 1000).max(1) as usize,
+            hold_samples: (sample_rate as f64 * GATE_HOLD_SECS) as u64,
+            hold_left: 0,
+        }
+    }
+
+    /// Whether `samples` should be sent: any frame at or above the threshold opens
+    /// the gate, and it stays open for [`GATE_HOLD_SECS`] after the last one
+    pub fn admit(&mut self, samples: &[i16]) -> bool {
+        if samples.chunks(self.frame_len).any(|f| frame_rms(f) >= self.threshold) {
+            self.hold_left = self.hold_samples;
+            true
+        } else if self.hold_left > 0 {
+            self.hold_left = self.hold_left.saturating_sub(samples.len() as u64);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1000;
+
+    fn loud(ms: usize) -> Vec<i16> {
+        vec![10000; ms * RATE as usize / 1000]
+    }
+
+    fn quiet(ms: usize) -> Vec<i16> {
+        vec![0; ms * RATE as usize / 1000]
+    }
+
+    fn assert_span(span: SpeechSpan, start: f64, end: f64) {
+        assert!((span.start - start).abs() < 1e-9 && (span.end - end).abs() < 1e-9, "got {:?}", span);
+    }
+
+    #[test]
+    fn gate_opens_holds_and_closes() {
+        let mut gate = NoiseGate::new(0.1, RATE);
+        assert!(!gate.admit(&quiet(200)));
+
+        // one loud frame is enough to open it
+        let mut batch = quiet(200);
+        batch[100..120].copy_from_slice(&loud(20));
+        assert!(gate.admit(&batch));
+
+        // held for GATE_HOLD_SECS (600 ms) of quiet audio, then closed
+        assert!(gate.admit(&quiet(200)));
+        assert!(gate.admit(&quiet(200)));
+        assert!(gate.admit(&quiet(200)));
+        assert!(!gate.admit(&quiet(200)));
+
+        assert!(gate.admit(&loud(200)));
+    }
+
+    #[test]
+    fn short_silence_stays_within_one_span() {
+        let mut vad = VoiceActivity::new(RATE);
+        assert!(vad.push(&loud(500)).is_empty());
+        // shorter than the hangover: the span stays open
+        assert!(vad.push(&quiet(200)).is_empty());
+        assert!(vad.push(&loud(200)).is_empty());
+
+        let closed = vad.push(&quiet(400));
+        assert_eq!(closed.len(), 1);
+        assert_span(closed[0], 0.0, 0.9);
+        assert_eq!(vad.finish(), None);
+    }
+
+    #[test]
+    fn spans_shorter_than_the_minimum_are_dropped() {
+        let mut vad = VoiceActivity::new(RATE);
+        vad.push(&loud(100));
+        assert!(vad.push(&quiet(400)).is_empty());
+
+        vad.push(&loud(100));
+        assert_eq!(vad.finish(), None);
+    }
+
+    #[test]
+    fn finish_closes_the_open_span() {
+        let mut vad = VoiceActivity::new(RATE);
+        vad.push(&quiet(100));
+        // frames split across pushes are still analysed whole
+        vad.push(&loud(150));
+        vad.push(&loud(150));
+        assert_span(vad.finish().expect("span"), 0.1, 0.4);
+        assert_eq!(vad.finish(), None);
+    }
+}
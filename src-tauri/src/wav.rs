@@ -0,0 +1,183 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor};
+use std::path::Path;
+
+fn pcm16_spec(sample_rate: u32, channels: u16) -> hound::WavSpec {
+    hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+/// 💾 Write a complete mono 16-bit PCM WAV file
+#[cfg(test)]
+pub fn write_pcm16_mono(path: &Path, samples: &[i16], sample_rate: u32) -> Result<(), String> {
+    let mut writer = WavStreamWriter::create(path, sample_rate, 1)?;
+    writer.write(samples)?;
+    writer.finalize()
+}
+
+/// 📂 Read a 16-bit PCM WAV file → (interleaved samples, sample_rate, channels)
+#[cfg(test)]
+pub fn read_pcm16(path: &Path) -> Result<(Vec<i16>, u32, u16), String> {
+    let reader = hound::WavReader::open(path).map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(format!(
+            "Unsupported WAV format: {:?} {}-bit (expected 16-bit PCM)",
+            spec.sample_format, spec.bits_per_sample
+        ));
+    }
+    let samples = reader
+        .into_samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read WAV samples: {}", e))?;
+    Ok((samples, spec.sample_rate, spec.channels))
+}
+
+/// 📦 Encode interleaved samples as an in-memory 16-bit PCM WAV file
+pub fn encode_pcm16(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<u8>, String> {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, pcm16_spec(sample_rate, channels))
+        .map_err(|e| format!("Failed to encode WAV: {}", e))?;
+    for &s in samples {
+        writer.write_sample(s).map_err(|e| format!("Failed to encode WAV: {}", e))?;
+    }
+    writer.finalize().map_err(|e| format!("Failed to encode WAV: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// 📂 Reads a 16-bit PCM WAV file a piece at a time (long files don't fit in memory)
+pub struct Pcm16Chunks {
+    reader: hound::WavReader<BufReader<File>>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Total length in frames (samples per channel)
+    pub frames: u64,
+}
+
+impl Pcm16Chunks {
+    /// Open `path`; fails for anything but 16-bit integer PCM
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let reader = hound::WavReader::open(path).map_err(|e| format!("Failed to open WAV: {}", e))?;
+        let spec = reader.spec();
+        if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+            return Err(format!(
+                "Unsupported WAV format: {:?} {}-bit (expected 16-bit PCM)",
+                spec.sample_format, spec.bits_per_sample
+            ));
+        }
+        let frames = reader.duration() as u64;
+        Ok(Pcm16Chunks { reader, sample_rate: spec.sample_rate, channels: spec.channels, frames })
+    }
+
+    /// Next `frames` frames, interleaved (shorter at the end, empty once exhausted)
+    pub fn next_chunk(&mut self, frames: usize) -> Result<Vec<i16>, String> {
+        self.reader
+            .samples::<i16>()
+            .take(frames * self.channels as usize)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read WAV samples: {}", e))
+    }
+}
+
+/// 📼 Incremental 16-bit PCM WAV writer; the header sizes are fixed up on `finalize`
+pub struct WavStreamWriter {
+    inner: hound::WavWriter<BufWriter<File>>,
+}
+
+impl WavStreamWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> Result<Self, String> {
+        let inner = hound::WavWriter::create(path, pcm16_spec(sample_rate, channels))
+            .map_err(|e| format!("Failed to create WAV: {}", e))?;
+        Ok(WavStreamWriter { inner })
+    }
+
+    /// Append interleaved samples
+    pub fn write(&mut self, samples: &[i16]) -> Result<(), String> {
+        let mut w = self.inner.get_i16_writer(samples.len() as u32);
+        for &s in samples {
+            w.write_sample(s);
+        }
+        w.flush().map_err(|e| format!("Failed to write WAV samples: {}", e))
+    }
+
+    /// Flush and write the final RIFF/data chunk sizes
+    pub fn finalize(self) -> Result<(), String> {
+        self.inner.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("heard_it_wav_{}_{}.wav", name, std::process::id()))
+    }
+
+    fn u32_at(b: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]])
+    }
+
+    fn u16_at(b: &[u8], i: usize) -> u16 {
+        u16::from_le_bytes([b[i], b[i + 1]])
+    }
+
+    #[test]
+    fn mono_round_trip() {
+        let path = temp_path("mono");
+        let samples: Vec<i16> = vec![0, 1, -1, i16::MAX, i16::MIN, 1234, -4321];
+        write_pcm16_mono(&path, &samples, 16000).unwrap();
+
+        let (read, rate, channels) = read_pcm16(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(read, samples);
+        assert_eq!(rate, 16000);
+        assert_eq!(channels, 1);
+    }
+
+    #[test]
+    fn riff_header_fields() {
+        let path = temp_path("header");
+        let samples = vec![7i16; 100];
+        write_pcm16_mono(&path, &samples, 44100).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..12], b"WAVE");
+        assert_eq!(&bytes[12..16], b"fmt ");
+        assert_eq!(u32_at(&bytes, 16), 16); // fmt chunk size
+        assert_eq!(u16_at(&bytes, 20), 1); // PCM
+        assert_eq!(u16_at(&bytes, 22), 1); // channels
+        assert_eq!(u32_at(&bytes, 24), 44100); // sample rate
+        assert_eq!(u32_at(&bytes, 28), 44100 * 2); // byte rate
+        assert_eq!(u16_at(&bytes, 32), 2); // block align
+        assert_eq!(u16_at(&bytes, 34), 16); // bits per sample
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 200);
+    }
+
+    #[test]
+    fn streaming_writer_finalizes_stereo() {
+        let path = temp_path("stream");
+        let mut w = WavStreamWriter::create(&path, 48000, 2).unwrap();
+        w.write(&[1, -1, 2, -2]).unwrap();
+        w.write(&[3, -3]).unwrap();
+        w.finalize().unwrap();
+
+        let (read, rate, channels) = read_pcm16(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(read, vec![1, -1, 2, -2, 3, -3]);
+        assert_eq!(rate, 48000);
+        assert_eq!(channels, 2);
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::audio::meter::downmix_to_mono;
+use crate::audio::queue::FrameSender;
+use crate::deepgram::resampler::Resampler;
+use crate::session;
+use crate::wav::Pcm16Chunks;
+
+/// Files are streamed as 16 kHz mono, like a default live session
+pub const STREAM_RATE: u32 = 16000;
+/// Audio per frame, as a mic callback would deliver it
+const FRAME_MS: u64 = 20;
+/// Fastest accepted playback speed
+pub const MAX_SPEED: f32 = 20.0;
+
+/// Reject speeds that are not a positive multiplier up to [`MAX_SPEED`]
+pub fn validate_speed(speed: f32) -> Result<(), String> {
+    if speed.is_finite() && speed > 0.0 && speed <= MAX_SPEED {
+        Ok(())
+    } else {
+        Err(format!("Playback speed must be between 0 and {} (got {})", MAX_SPEED, speed))
+    }
+}
+
+/// 📼 Play a 16-bit PCM WAV file into `sender` as [`STREAM_RATE`] mono frames, paced
+/// like a live mic at `speed` × real time.
+///
+/// Stops early once the session is no longer active (`stop_recording`), and holds
+/// its position while paused. Returns the seconds of audio sent.
+pub async fn feed(path: &Path, sender: FrameSender, speed: f32) -> Result<f64, String> {
+    validate_speed(speed)?;
+    let mut chunks = Pcm16Chunks::open(path)?;
+    let channels = chunks.channels.max(1) as usize;
+    let frames_per_chunk = (chunks.sample_rate as u64 * FRAME_MS / 1000).max(1) as usize;
+    println!(
+        "📼 Streaming {:?} ({} Hz x{}, {:.1}s) at {}x",
+        path,
+        chunks.sample_rate,
+        channels,
+        chunks.frames as f64 / chunks.sample_rate.max(1) as f64,
+        speed
+    );
+
+    let mut resampler = Resampler::new(chunks.sample_rate, STREAM_RATE);
+    let mut sent = 0usize;
+    // Paced against a clock that stops while paused, so a pause doesn't cause a burst after it
+    let mut clock = Instant::now();
+    loop {
+        while session::is_paused() {
+            let paused_at = Instant::now();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            clock += paused_at.elapsed();
+        }
+        if !session::state().is_active() {
+            println!("🛑 WAV stream stopped");
+            break;
+        }
+
+        let chunk = chunks.next_chunk(frames_per_chunk)?;
+        if chunk.is_empty() {
+            break;
+        }
+        let frame = resampler.push_and_resample(&downmix_to_mono(&chunk, channels));
+        sent += frame.len();
+        if !frame.is_empty() && !sender.send(frame) {
+            return Err("Transcription task ended early".into());
+        }
+
+        let due = Duration::from_secs_f64(sent as f64 / STREAM_RATE as f64 / speed as f64);
+        tokio::time::sleep_until(clock + due).await;
+    }
+    Ok(sent as f64 / STREAM_RATE as f64)
+}
@@ -0,0 +1,244 @@
+use futures_util::future::BoxFuture;
+use std::io::Cursor;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::Receiver;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState};
+
+use crate::audio::meter::downmix_to_mono;
+use crate::backend::TranscriptionBackend;
+use crate::deepgram::resampler::Resampler;
+use crate::deepgram::TranscriptionError;
+use crate::model::{FileTranscript, Segment, TranscriptEvent};
+use crate::options::{FileOptions, RecordingOptions, DEFAULT_LANGUAGE};
+use crate::transcript;
+
+/// Whisper only accepts 16 kHz mono
+const WHISPER_RATE: u32 = 16000;
+/// Live audio is transcribed in windows of this length
+const WINDOW_SECS: usize = 5;
+/// whisper.cpp rejects shorter input; tails are padded with silence up to this
+const MIN_INPUT_SECS: usize = 1;
+
+/// 🦙 Local whisper.cpp as a [`TranscriptionBackend`] (nothing leaves the machine)
+pub struct WhisperBackend {
+    model_path: String,
+}
+
+impl WhisperBackend {
+    /// Fails when no model is configured (`whisper_model_path`) or the file is missing
+    pub fn new() -> Result<Self, String> {
+        let model_path = crate::config::load()
+            .whisper_model_path
+            .filter(|p| !p.trim().is_empty())
+            .ok_or("Whisper model not configured (set whisper_model_path)")?;
+        if !std::path::Path::new(&model_path).is_file() {
+            return Err(format!("Whisper model not found: {}", model_path));
+        }
+        Ok(WhisperBackend { model_path })
+    }
+}
+
+/// Load the model and create an inference state (slow: run off the async runtime)
+fn load_state(model_path: &str) -> Result<WhisperState, String> {
+    println!("🦙 Loading Whisper model {}", model_path);
+    let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+        .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
+    ctx.create_state().map_err(|e| format!("Failed to create Whisper state: {}", e))
+}
+
+/// Run Whisper over 16 kHz mono `samples`; segments are offset by `offset` seconds
+fn infer(state: &mut WhisperState, samples: &[i16], offset: f64, language: &str, prompt: Option<&str>) -> Result<Vec<Segment>, String> {
+    let mut audio: Vec<f32> = samples.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+    audio.resize(audio.len().max(WHISPER_RATE as usize * MIN_INPUT_SECS), 0.0);
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language));
+    if let Some(prompt) = prompt {
+        params.set_initial_prompt(prompt);
+    }
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    params.set_suppress_blank(true);
+
+    state.full(params, &audio).map_err(|e| format!("Whisper inference failed: {}", e))?;
+    let count = state.full_n_segments().map_err(|e| e.to_string())?;
+    let mut segments = Vec::new();
+    for i in 0..count {
+        let text = state.full_get_segment_text(i).map_err(|e| e.to_string())?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        // Timestamps come in centiseconds
+        let t0 = state.full_get_segment_t0(i).map_err(|e| e.to_string())? as f64 / 100.0;
+        let t1 = state.full_get_segment_t1(i).map_err(|e| e.to_string())? as f64 / 100.0;
+        segments.push(Segment { start: offset + t0, end: offset + t1, text: text.trim().to_string(), speaker: None, channel: None });
+    }
+    Ok(segments)
+}
+
+/// Resample interleaved audio to Whisper's 16 kHz mono
+fn to_whisper_input(resampler: &mut Resampler, samples: &[i16], channels: usize) -> Vec<i16> {
+    resampler.push_and_resample(&downmix_to_mono(samples, channels))
+}
+
+/// Transcribe one window off the runtime, handing the state back afterwards;
+/// `None` if the inference thread panicked (the state is gone with it)
+async fn transcribe_window(
+    mut state: WhisperState,
+    window: Vec<i16>,
+    offset: f64,
+    options: Arc<RecordingOptions>,
+) -> Option<(WhisperState, Result<Vec<Segment>, String>)> {
+    tokio::task::spawn_blocking(move || {
+        let result = infer(&mut state, &window, offset, options.language(), options.context.as_deref());
+        (state, result)
+    })
+    .await
+    .ok()
+}
+
+/// Emit a window's segments like Deepgram finals
+fn emit_segments(app: &AppHandle, segments: Vec<Segment>, legacy_event: bool) {
+    for segment in segments {
+        transcript::emit(app, TranscriptEvent {
+            text: segment.text.clone(),
+            is_final: true,
+            confidence: None,
+            start: Some(segment.start),
+            end: Some(segment.end),
+            speaker: None,
+            t_start: crate::session::elapsed_secs(),
+        }, legacy_event);
+        transcript::append_segment(segment);
+    }
+}
+
+/// 🦙 Transcribe live audio in [`WINDOW_SECS`] windows until `rx` closes
+async fn stream_to_whisper(
+    mut rx: Receiver<Vec<i16>>,
+    app: AppHandle,
+    model_path: String,
+    sample_rate: u32,
+    channels: u16,
+    options: RecordingOptions,
+) {
+    let loaded = tokio::task::spawn_blocking(move || load_state(&model_path)).await;
+    let mut state = match loaded {
+        Ok(Ok(state)) => state,
+        Ok(Err(e)) => {
+            eprintln!("❌ {}", e);
+            let _ = app.emit("error", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("❌ Whisper model loader crashed: {}", e);
+            return;
+        }
+    };
+    println!("✅ Whisper model loaded");
+
+    let options = Arc::new(options);
+    let legacy_event = crate::config::load().legacy_transcript_event;
+    let channels = channels.max(1) as usize;
+    let mut resampler = Resampler::new(sample_rate, WHISPER_RATE);
+    let window_len = WHISPER_RATE as usize * WINDOW_SECS;
+    let mut pending: Vec<i16> = Vec::new();
+    // Session time of pending[0]
+    let mut offset = 0.0;
+
+    loop {
+        let chunk = rx.recv().await;
+        let finished = chunk.is_none();
+        if let Some(chunk) = chunk {
+            // The WAV copy (if any) gets the device-rate audio, as with Deepgram
+            crate::recorder::write(&chunk);
+            pending.extend(to_whisper_input(&mut resampler, &chunk, channels));
+        }
+
+        // Whole windows while recording; after the last chunk, whatever is left
+        while pending.len() >= window_len || (finished && !pending.is_empty()) {
+            let take = pending.len().min(window_len);
+            let window: Vec<i16> = pending.drain(..take).collect();
+            let window_offset = offset;
+            offset += take as f64 / WHISPER_RATE as f64;
+            let Some((returned, result)) = transcribe_window(state, window, window_offset, options.clone()).await else {
+                eprintln!("❌ Whisper inference crashed; stopping transcription");
+                return;
+            };
+            state = returned;
+            match result {
+                Ok(segments) => emit_segments(&app, segments, legacy_event),
+                Err(message) => {
+                    eprintln!("❌ {}", message);
+                    let _ = app.emit("transcription_error", TranscriptionError { message, request_id: None });
+                }
+            }
+        }
+        if finished {
+            break;
+        }
+    }
+}
+
+impl TranscriptionBackend for WhisperBackend {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    fn stream(
+        &self,
+        rx: Receiver<Vec<i16>>,
+        app: AppHandle,
+        sample_rate: u32,
+        channels: u16,
+        options: RecordingOptions,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(stream_to_whisper(rx, app, self.model_path.clone(), sample_rate, channels, options))
+    }
+
+    /// Only PCM WAV files: other containers would need a decoder
+    fn transcribe_file(
+        &self,
+        audio: Vec<u8>,
+        mime: Option<&'static str>,
+        _options: &FileOptions,
+    ) -> BoxFuture<'static, Result<FileTranscript, String>> {
+        let model_path = self.model_path.clone();
+        Box::pin(async move {
+            if mime.is_some_and(|m| m != "audio/wav") {
+                return Err(format!("Local Whisper only transcribes WAV files (got {})", mime.unwrap_or_default()));
+            }
+            tokio::task::spawn_blocking(move || {
+                let reader = hound::WavReader::new(Cursor::new(audio)).map_err(|e| format!("Failed to open WAV: {}", e))?;
+                let spec = reader.spec();
+                if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+                    return Err(format!("Unsupported WAV format: {:?} {}-bit (expected 16-bit PCM)", spec.sample_format, spec.bits_per_sample));
+                }
+                let samples = reader
+                    .into_samples::<i16>()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to read WAV samples: {}", e))?;
+                let mut resampler = Resampler::new(spec.sample_rate, WHISPER_RATE);
+                let input = to_whisper_input(&mut resampler, &samples, spec.channels.max(1) as usize);
+
+                let mut state = load_state(&model_path)?;
+                let segments = infer(&mut state, &input, 0.0, DEFAULT_LANGUAGE, None)?;
+                Ok(FileTranscript {
+                    file_name: String::new(),
+                    text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+                    duration: Some(input.len() as f64 / WHISPER_RATE as f64),
+                    segments,
+                    words: Vec::new(),
+                    paragraphs: Vec::new(),
+                    summary: None,
+                    detected_language: None,
+                })
+            })
+            .await
+            .map_err(|e| format!("Whisper inference crashed: {}", e))?
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use crate::audio::queue::FrameSender;
+
+/// 🔎 Locate the worker binary next to the current exe (falls back to PATH)
+pub fn worker_path() -> PathBuf {
+    let worker_name = if cfg!(windows) { "audio_worker.exe" } else { "audio_worker" };
+    if let Ok(p) = std::env::current_exe() {
+        if let Some(dir) = p.parent() {
+            let cand = dir.join(worker_name);
+            if cand.exists() {
+                return cand;
+            }
+        }
+    }
+    // fallback to just the name (assume in PATH)
+    PathBuf::from(worker_name)
+}
+
+/// The running worker, so it can be killed from anywhere (e.g. on app exit)
+static CHILD: Mutex<Option<Child>> = Mutex::new(None);
+
+/// Hand the running worker over to the global slot; returns its pid.
+/// A worker tracked before (which should already be dead) is killed.
+pub fn track(child: Child) -> u32 {
+    let pid = child.id();
+    if let Some(mut old) = CHILD.lock().unwrap().replace(child) {
+        let _ = old.kill();
+        let _ = old.wait();
+    }
+    pid
+}
+
+/// Whether the tracked worker `pid` has exited with status 0
+pub fn exited_cleanly(pid: u32) -> bool {
+    match CHILD.lock().unwrap().as_mut() {
+        Some(child) if child.id() == pid => matches!(child.try_wait(), Ok(Some(status)) if status.success()),
+        _ => false,
+    }
+}
+
+/// 🔪 Kill and reap the tracked worker (only if it is `pid`, when given)
+pub fn kill(pid: Option<u32>) {
+    let mut guard = CHILD.lock().unwrap();
+    if pid.is_some_and(|pid| guard.as_ref().map(Child::id) != Some(pid)) {
+        return;
+    }
+    if let Some(mut child) = guard.take() {
+        println!("🔪 Stopping audio_worker (pid={})", child.id());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Magic of the request written to the worker's stdin (see `audio_worker.rs`)
+const REQUEST_MAGIC: &[u8; 4] = b"RQST";
+/// Sent after both magics; bumped on any incompatible change to the framing
+pub const PROTOCOL_VERSION: u8 = 1;
+/// Longest frame accepted from the worker, in samples; anything longer means the
+/// stream is corrupt or out of sync
+pub const MAX_FRAME_SAMPLES: usize = 1 << 20;
+
+/// 🎛️ Capture format asked of (`spawn`) or reported by (`read_header`) the worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// 🔌 Spawn the worker with `--host <id> --device <name>`, stdout piped for framed PCM, and send
+/// it the wanted format. The worker falls back to the device's default config when
+/// the device can't do it.
+///
+/// Its stdin stays open after the request: the worker exits when it reaches EOF,
+/// so it can't outlive us even if it is never killed.
+pub fn spawn(path: &Path, host: &str, device: &str, wanted: WorkerFormat) -> std::io::Result<Child> {
+    let mut child = Command::new(path)
+        .arg("--host")
+        .arg(host)
+        .arg("--device")
+        .arg(device)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    let mut request = Vec::with_capacity(11);
+    request.extend_from_slice(REQUEST_MAGIC);
+    request.push(PROTOCOL_VERSION);
+    request.extend_from_slice(&wanted.sample_rate.to_le_bytes());
+    request.extend_from_slice(&wanted.channels.to_le_bytes());
+    let sent = match child.stdin.as_mut() {
+        Some(stdin) => stdin.write_all(&request).and_then(|_| stdin.flush()),
+        None => Err(std::io::Error::other("audio_worker spawned without stdin")),
+    };
+    if let Err(e) = sent {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e);
+    }
+    Ok(child)
+}
+
+/// Read the header (magic + version + sample_rate + channels) and return the format the worker opened
+pub fn read_header(out: &mut impl Read) -> Result<WorkerFormat, String> {
+    let mut header = [0u8; 11];
+    out.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if &header[0..4] != b"SRAT" {
+        return Err("audio_worker sent invalid header".into());
+    }
+    if header[4] != PROTOCOL_VERSION {
+        return Err(format!("audio_worker speaks protocol v{} (expected v{})", header[4], PROTOCOL_VERSION));
+    }
+    let sr = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+    let channels = u16::from_le_bytes([header[9], header[10]]);
+    Ok(WorkerFormat { sample_rate: if sr == 0 { 16000 } else { sr }, channels: channels.max(1) })
+}
+
+/// Why the worker header couldn't be read
+pub enum HeaderError {
+    /// Nothing arrived before the deadline (worker hung while opening the device)
+    Timeout,
+    Failed(String),
+}
+
+/// Read the header on a helper thread, giving up after `timeout`.
+///
+/// On timeout the reader thread stays blocked until the caller kills the child
+/// (closing the pipe), after which it exits on its own.
+pub fn read_header_timeout(out: ChildStdout, timeout: Duration) -> Result<(ChildStdout, WorkerFormat), HeaderError> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut out = out;
+        let res = read_header(&mut out);
+        let _ = tx.send(res.map(|format| (out, format)));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(res) => res.map_err(HeaderError::Failed),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(HeaderError::Timeout),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(HeaderError::Failed("header reader exited".into())),
+    }
+}
+
+/// Spawn + header read in one step; the child is killed if the header can't be read
+pub fn start(
+    path: &Path,
+    host: &str,
+    device: &str,
+    wanted: WorkerFormat,
+    header_timeout: Duration,
+) -> Result<(Child, ChildStdout, WorkerFormat), String> {
+    let mut child = spawn(path, host, device, wanted).map_err(|e| format!("Failed to spawn audio_worker {:?}: {}", path, e))?;
+    let Some(out) = child.stdout.take() else {
+        let _ = child.kill();
+        return Err("audio_worker spawned without stdout".into());
+    };
+    match read_header_timeout(out, header_timeout) {
+        Ok((out, format)) => Ok((child, out, format)),
+        Err(HeaderError::Timeout) => {
+            let _ = child.kill();
+            Err(format!("Timed out after {:?} waiting for audio_worker header", header_timeout))
+        }
+        Err(HeaderError::Failed(e)) => {
+            let _ = child.kill();
+            Err(format!("Failed to read header from audio_worker: {}", e))
+        }
+    }
+}
+
+/// Why `forward_frames` stopped
+pub enum ForwardEnd {
+    /// The audio channel was closed on our side
+    ReceiverClosed,
+    /// The worker pipe broke or hit EOF
+    ReadError,
+    /// The worker sent something that isn't a valid frame (the stream is out of sync)
+    ProtocolError(String),
+    /// The session's stop flag was raised
+    Stopped,
+}
+
+/// Read length-prefixed i16 frames from the worker and forward them until either side
+/// closes or `stop` is set (checked between frames). Every frame goes through
+/// `process` (input gain, metering); frames read while `paused` is set are then
+/// discarded, so the worker never blocks on a full pipe.
+pub fn forward_frames(
+    reader: &mut impl Read,
+    sender: &FrameSender,
+    stop: &AtomicBool,
+    paused: &AtomicBool,
+    mut process: impl FnMut(&mut [i16]),
+) -> ForwardEnd {
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return ForwardEnd::Stopped;
+        }
+
+        // read frame length (u32 LE)
+        let mut lenb = [0u8; 4];
+        if let Err(e) = reader.read_exact(&mut lenb) {
+            eprintln!("audio_worker read error (len): {}", e);
+            return ForwardEnd::ReadError;
+        }
+        let len = u32::from_le_bytes(lenb) as usize;
+        if len > MAX_FRAME_SAMPLES {
+            return ForwardEnd::ProtocolError(format!("frame of {} samples exceeds the {} limit", len, MAX_FRAME_SAMPLES));
+        }
+        let mut buf = vec![0u8; len * 2];
+        if let Err(e) = reader.read_exact(&mut buf) {
+            eprintln!("audio_worker read error (payload): {}", e);
+            return ForwardEnd::ReadError;
+        }
+        // convert to i16 samples
+        let mut samples: Vec<i16> = buf.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+
+        process(&mut samples);
+        if paused.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        // send to channel
+        if !sender.send(samples) {
+            eprintln!("Failed to forward audio frame; receiver closed");
+            return ForwardEnd::ReceiverClosed;
+        }
+    }
+}
@@ -1,14 +1,34 @@
 import { useEffect, useState, useRef } from "react";
-import { Mic, MicOff, Upload, Download } from "lucide-react";
+import { Mic, MicOff, Upload, Download, Copy, ListChecks } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import "./App.css";
 
+/** Structured `transcript` event payload (see `TranscriptEvent` in model.rs) */
+type TranscriptEvent = {
+  text: string;
+  is_final: boolean;
+  confidence?: number | null;
+  start?: number | null;
+  end?: number | null;
+  speaker?: number | null;
+  /** Seconds since recording started when the result arrived (live only) */
+  t_start?: number | null;
+};
+
+/** Format seconds as a `[hh:mm:ss]` prefix */
+const stamp = (secs: number) => {
+  const t = Math.floor(secs);
+  const pad = (n: number) => String(n).padStart(2, "0");
+  return `[${pad(Math.floor(t / 3600))}:${pad(Math.floor(t / 60) % 60)}:${pad(t % 60)}] `;
+};
+
 export default function App() {
   // 🎙 Mic state
   const [isRecording, setIsRecording] = useState(false);
   const [micDevices, setMicDevices] = useState<string[]>([]);
   const [selectedMic, setSelectedMic] = useState<string>("");
+  const [hasApiKey, setHasApiKey] = useState(true);
 
   // 📝 Transcript
   const [liveText, setLiveText] = useState("");
@@ -19,6 +39,11 @@ export default function App() {
 
   // ⏳ Progress
   const [isProcessing, setIsProcessing] = useState(false);
+  // Provider for file uploads ("deepgram" or "openai")
+  const [fileBackend, setFileBackend] = useState("deepgram");
+  // Let Deepgram work out the spoken language of uploads
+  const [detectLanguage, setDetectLanguage] = useState(false);
+  const [detectedLanguage, setDetectedLanguage] = useState<string | null>(null);
 
   const [levels, setLevels] = useState<number[]>([]);
   const canvasRef = useRef<HTMLCanvasElement | null>(null);
@@ -50,6 +75,22 @@ export default function App() {
       } catch { /* ignore */ }
     }
 
+    invoke<boolean>("has_api_key").then(setHasApiKey).catch(console.error);
+
+    // Offer back a recording the app crashed in the middle of
+    invoke<{ session_id: string; created_at: number; entries: string[] } | null>("check_for_recoverable_session")
+      .then(async (session) => {
+        if (!session) return;
+        const when = new Date(session.created_at * 1000).toLocaleString();
+        const restore = confirm(`A recording from ${when} was not stopped properly. Restore its transcript?`);
+        const entries = await invoke<string[]>("resolve_recoverable_session", {
+          sessionId: session.session_id,
+          restore,
+        });
+        if (restore) setFinalText(entries.join("\n\n"));
+      })
+      .catch((e) => console.warn("Session recovery check failed:", e));
+
     invoke<string[]>("list_mic_devices")
       .then((devices) => {
         console.log("🎤 Available mics:", devices);
@@ -67,12 +108,15 @@ export default function App() {
      🎙 START / STOP MIC
   ============================ */
   const toggleRecording = async () => {
-    if (!selectedMic) return;
+    if (!selectedMic || (!isRecording && !hasApiKey)) return;
 
     try {
       if (!isRecording) {
         setFinalText("");
         setLiveText("");
+        await invoke("begin_session", { device: selectedMic }).catch((e) =>
+          console.warn("Failed to start history session:", e)
+        );
         await invoke("start_recording", { device: selectedMic });
         setIsRecording(true);
       } else {
@@ -81,6 +125,7 @@ export default function App() {
       }
     } catch (e) {
       console.error(e);
+      if (!isRecording) alert(`Could not start recording: ${e}`);
     }
   };
 
@@ -91,18 +136,35 @@ export default function App() {
     setIsProcessing(true);
     setFinalText("");
     setLiveText("");
+    setDetectedLanguage(null);
 
-    await invoke("pick_and_transcribe_file");
+    try {
+      const picked = await invoke<string | null>("transcribe_file_cmd", {
+        backend: fileBackend,
+        options: { detect_language: detectLanguage },
+      });
+      if (!picked) setIsProcessing(false);
+    } catch (e) {
+      console.error(e);
+      setIsProcessing(false);
+      alert(`Could not transcribe file: ${e}`);
+    }
   };
 
   /* ===========================
      🧠 LISTEN FOR TRANSCRIPTS
   ============================ */
   useEffect(() => {
-    const unlisten = listen<string>("transcript", (e) => {
+    const unlisten = listen<TranscriptEvent | string>("transcript", (e) => {
       console.log("📝 Transcript received:", e.payload);
+      // Bare strings come from the backend's legacy_transcript_event mode
+      const payload =
+        typeof e.payload === "string" ? { text: e.payload, is_final: true } : e.payload;
+      if (!payload.is_final) return;
+      // New lines of a live session get a timestamp prefix
+      const prefix = "t_start" in payload && payload.t_start != null ? stamp(payload.t_start) : "";
       setFinalText((prev) =>
-        prev ? prev + " " + e.payload : e.payload
+        !prev || prev.endsWith("\n") ? prev + prefix + payload.text : prev + " " + payload.text
       );
       setIsProcessing(false);
     });
@@ -112,6 +174,59 @@ export default function App() {
     };
   }, []);
 
+  // Deepgram saw a pause after the last word: start a new paragraph
+  useEffect(() => {
+    const un = listen("utterance_end", () => {
+      setFinalText((prev) => (prev && !prev.endsWith("\n") ? prev + "\n\n" : prev));
+    });
+    return () => { un.then((u) => u()); };
+  }, []);
+
+  // Keep the mic list current as devices are plugged in or removed
+  useEffect(() => {
+    invoke("start_device_watcher").catch(console.error);
+    const un = listen<string[]>("devices_changed", (e) => {
+      setMicDevices(e.payload);
+      setSelectedMic((prev) => (e.payload.includes(prev) ? prev : e.payload[0] ?? ""));
+    });
+    return () => {
+      un.then((u) => u());
+      invoke("stop_device_watcher").catch(console.error);
+    };
+  }, []);
+
+  // Mic unplugged: the backend tries the default device, or gives up
+  useEffect(() => {
+    const unDisconnected = listen<{ device: string }>("device_disconnected", (e) => {
+      console.warn("🔌 Mic disconnected:", e.payload.device);
+    });
+    // Transcription fell behind (e.g. slow network) and some audio was dropped
+    const unOverrun = listen<{ dropped_frames: number }>("audio_overrun", (e) => {
+      console.warn("⚠️ Audio overrun, frames dropped:", e.payload.dropped_frames);
+    });
+    const unStopped = listen<{ reason: string }>("recording_stopped", async (e) => {
+      await invoke("stop_recording").catch(console.error);
+      setIsRecording(false);
+      alert(`Recording stopped: ${e.payload.reason}`);
+    });
+    return () => {
+      unDisconnected.then((u) => u());
+      unOverrun.then((u) => u());
+      unStopped.then((u) => u());
+    };
+  }, []);
+
+  // Language Deepgram detected in an uploaded file
+  useEffect(() => {
+    const un = listen<{ language: string; confidence: number | null }>("detected_language", (e) => {
+      const { language, confidence } = e.payload;
+      setDetectedLanguage(confidence != null ? `${language} (${Math.round(confidence * 100)}%)` : language);
+    });
+    return () => {
+      un.then((u) => u());
+    };
+  }, []);
+
   // Listen for audio level events from backend
   useEffect(() => {
     const un = listen<number>("audio_level", (e) => {
@@ -137,7 +252,7 @@ export default function App() {
     };
     window.addEventListener("keydown", onKey);
     return () => window.removeEventListener("keydown", onKey);
-  }, [selectedMic, isRecording]);
+  }, [selectedMic, isRecording, hasApiKey]);
 
   /* ===========================
      🗂 SAVE HISTORY
@@ -153,6 +268,12 @@ export default function App() {
     localStorage.setItem("transcriptHistory", JSON.stringify(history));
   }, [history]);
 
+  // keep this recording's session file up to date as results arrive (crash-safe)
+  useEffect(() => {
+    if (!isRecording || !finalText) return;
+    invoke("autosave_session").catch((e) => console.warn("Failed to auto-save session:", e));
+  }, [finalText, isRecording]);
+
   // auto save history to app data folder (no dialog)
   useEffect(() => {
     if (history.length === 0) return;
@@ -195,19 +316,33 @@ export default function App() {
   }, [selectedMic]);
 
   /* ===========================
-     ⬇ EXPORT
+     ⬇ EXPORT (Rust writes its own copy of the session transcript)
   ============================ */
   const exportTxt = () =>
-    invoke("export_txt", { transcript: finalText });
+    invoke("export_txt");
 
   const exportMd = () =>
-    invoke("export_md", { transcript: finalText });
+    invoke("export_md");
+
+  // Subtitle readability: 2 lines of 42 characters, at most 6s on screen
+  const cueLimits = { max_line_chars: 42, max_lines: 2, max_secs: 6 };
 
   const exportSrt = () =>
-    invoke("export_srt", { transcript: finalText });
+    invoke("export_srt", { limits: cueLimits });
 
   const exportVtt = () =>
-    invoke("export_vtt", { transcript: finalText });
+    invoke("export_vtt", { limits: cueLimits });
+
+  const exportCsv = () =>
+    invoke("export_csv");
+
+  const summarize = () =>
+    invoke<string[]>("summarize_session")
+      .then((points) => alert(points.map((p) => `• ${p}`).join("\n")))
+      .catch((e) => alert(`Could not summarize: ${e}`));
+
+  const copyTranscript = () =>
+    invoke("copy_to_clipboard", { text: finalText }).catch((e) => alert(`Could not copy: ${e}`));
 
   /* ===========================
      🖼 UI
@@ -247,7 +382,8 @@ export default function App() {
       {/* 🎙 Mic Button */}
       <button
         className={`mic-btn ${isRecording ? "recording" : ""}`}
-        disabled={!selectedMic}
+        disabled={!selectedMic || (!isRecording && !hasApiKey)}
+        title={hasApiKey ? undefined : "Deepgram API key not configured"}
         onClick={toggleRecording}
       >
         {isRecording ? <Mic /> : <MicOff />}
@@ -260,9 +396,20 @@ export default function App() {
       </div>
 
       {/* 📂 Upload */}
-      <button className="upload-btn" onClick={uploadFile}>
-        <Upload /> Upload Audio
-      </button>
+      <div className="upload-row">
+        <select value={fileBackend} onChange={(e) => setFileBackend(e.target.value)}>
+          <option value="deepgram">Deepgram</option>
+          <option value="openai">OpenAI Whisper</option>
+        </select>
+        <label>
+          <input type="checkbox" checked={detectLanguage} onChange={(e) => setDetectLanguage(e.target.checked)} />
+          Detect language
+        </label>
+        <button className="upload-btn" onClick={uploadFile}>
+          <Upload /> Upload Audio
+        </button>
+        {detectedLanguage && <span className="detected-language">🌐 {detectedLanguage}</span>}
+      </div>
 
       {/* ⏳ Progress */}
       {isProcessing && (
@@ -274,7 +421,7 @@ export default function App() {
 
       {/* 📝 Transcript */}
       <div className="transcript-box">
-        <p>{finalText || liveText || "Your transcript will appear here…"}</p>
+        <p style={{ whiteSpace: "pre-wrap" }}>{finalText || liveText || "Your transcript will appear here…"}</p>
       </div>
 
       {/* ⬇ Export */}
@@ -292,6 +439,15 @@ export default function App() {
           <button onClick={exportVtt}>
             <Download /> VTT
           </button>
+          <button onClick={exportCsv}>
+            <Download /> CSV
+          </button>
+          <button onClick={copyTranscript}>
+            <Copy /> Copy
+          </button>
+          <button onClick={summarize}>
+            <ListChecks /> Summarize
+          </button>
         </div>
       )}
 